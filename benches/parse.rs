@@ -0,0 +1,147 @@
+use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+
+use chrono::prelude::{DateTime, FixedOffset, Utc};
+use chrono::Duration;
+use crowfall_parser::parse_rust;
+use crowfall_parser::options::ParseOptions;
+use crowfall_parser::intern::Interner;
+use crowfall_parser::tokenizer::{parse_event, parse_timestamp, split_line, Verb};
+use regex::Regex;
+use std::fs;
+
+/// The envelope regex and the row cascade as they were before the tokenizer, kept here as the baseline.
+struct RegexCascade {
+    event: Regex,
+    food: Regex,
+    self_resource: Regex,
+    resource: Regex,
+    dps: Regex,
+    heal: Regex,
+}
+
+impl RegexCascade {
+    fn new() -> RegexCascade {
+        RegexCascade {
+            event: Regex::new("([-0-9T:\\.]+Z).*Event=\\[(.*)\\]").unwrap(),
+            food: Regex::new("^Your meal restored You for ([0-9]+) food.$").unwrap(),
+            self_resource: Regex::new("^Your (.+) (restored|drained) You for ([0-9]+) (.+).$").unwrap(),
+            resource: Regex::new("^(.+) (restored|drained) You for ([0-9]+) (.+).$").unwrap(),
+            dps: Regex::new("^([^ ]+) ?(.+)? hit (.+) for ([0-9]+) ?(\\(([0-9]+) absorbed\\))? ?(([^\\(]+) damage)? ?(\\(Critical\\))?.$").unwrap(),
+            heal: Regex::new("^([^ ]+) (.+) healed (.+) for ([0-9]+)( \\(([0-9]+) absorbed\\))?( hit points)?( \\(Critical\\))?.$").unwrap(),
+        }
+    }
+
+    /// The names are interned as the parsers did, the amount of a hit or heal is returned.
+    fn parse_row(&self, row: &str, _dt: DateTime<FixedOffset>, names: &mut Interner) -> u32 {
+        if self.food.is_match(row) || self.resource.is_match(row) || self.self_resource.is_match(row) {
+            return 1;
+        }
+
+        if let Some(cap) = self.dps.captures(row) {
+            names.intern(&cap[1]);
+            names.intern(cap.get(2).map_or("", |spell| spell.as_str()));
+            names.intern(&cap[3]);
+            names.intern(cap.get(8).map_or("", |kind| kind.as_str()));
+            return cap[4].parse::<u32>().unwrap();
+        }
+
+        if let Some(cap) = self.heal.captures(row) {
+            names.intern(&cap[1]);
+            names.intern(&cap[2]);
+            names.intern(&cap[3]);
+            return cap[4].parse::<u32>().unwrap();
+        }
+
+        0
+    }
+
+    fn parse(&self, contents: &str) -> u32 {
+        let mut total = 0;
+        let mut names = Interner::new();
+
+        for line in contents.lines() {
+            if !self.event.is_match(line) {
+                continue;
+            }
+
+            for cap in self.event.captures_iter(line) {
+                let d = DateTime::parse_from_rfc3339(&cap[1]).unwrap();
+                total += self.parse_row(&cap[2], d, &mut names);
+            }
+        }
+
+        total
+    }
+}
+
+fn parse_tokenizer(contents: &str) -> u32 {
+    let mut total = 0;
+
+    for line in contents.lines() {
+        let (date, row) = match split_line(line) {
+            Some(split) => split,
+            None => continue,
+        };
+
+        let _d = parse_timestamp(date).unwrap();
+        total += match parse_event(row) {
+            Some(event) if event.verb == Verb::Hit || event.verb == Verb::Healed => event.amount,
+            Some(_) => 1,
+            None => 0,
+        };
+    }
+
+    total
+}
+
+fn bench_parse(c: &mut Criterion) {
+    let fixture = fs::read_to_string("./fixtures/file1.txt").unwrap();
+    let cascade = RegexCascade::new();
+
+    assert_eq!(cascade.parse(&fixture), parse_tokenizer(&fixture));
+
+    let mut group = c.benchmark_group("fixture");
+    group.bench_function("regex cascade", |b| b.iter(|| cascade.parse(black_box(&fixture))));
+    group.bench_function("tokenizer", |b| b.iter(|| parse_tokenizer(black_box(&fixture))));
+    group.finish();
+}
+
+/// A log of `events` lines, one every 100ms, with a 60s pause every 200 events so the number of fights grows with it.
+fn synthetic_log(events: usize) -> String {
+    let mut date = DateTime::parse_from_rfc3339("2021-03-31T04:35:00.000Z").unwrap().with_timezone(&Utc);
+    let mut log = String::with_capacity(events * 110);
+
+    for i in 0..events {
+        date = date + if i % 200 == 0 { Duration::seconds(60) } else { Duration::milliseconds(100) };
+
+        let event = match i % 4 {
+            0 => "Your Static Bolt hit Cleric Defender for 100 Nature damage.",
+            1 => "Cleric Defender Hammer hit You for 38 Fire damage.",
+            2 => "Your Death Surge healed You for 311 hit points.",
+            _ => "Ranger Defender Straight Shot hit You for 85 (12 absorbed) Fire damage (Critical).",
+        };
+
+        log.push_str(&format!("{} INFO    COMBAT    - Combat _||_ Event=[{}]\n", date.format("%Y-%m-%dT%H:%M:%S%.3fZ"), event));
+    }
+
+    log
+}
+
+fn bench_scaling(c: &mut Criterion) {
+    let mut group = c.benchmark_group("synthetic");
+    group.sample_size(10);
+
+    for events in [125_000, 250_000, 500_000, 1_000_000].iter() {
+        let log = synthetic_log(*events);
+        let options = ParseOptions { minimum_time: 0, ..Default::default() };
+        assert_eq!(parse_rust(&log, &options).fights.len(), events / 200);
+
+        group.throughput(Throughput::Elements(*events as u64));
+        group.bench_with_input(BenchmarkId::new("parse", events), &log, |b, log| b.iter(|| parse_rust(black_box(log), &options)));
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, bench_parse, bench_scaling);
+criterion_main!(benches);
@@ -0,0 +1,264 @@
+export type ActivityKind = "damage_dealt" | "damage_received" | "heal_done" | "heal_received" | "resource" | "food" | "custom";
+
+/** A parsed log and the character who wrote it. */
+export interface CharacterSession {
+    character: string;
+    data: ExportedData;
+}
+
+/** A user supplied grammar, the regex can use the named captures `emitter`, `spell`, `receiver`, `amount` and `kind`, all of them optional. */
+export interface CustomPattern {
+    name: string;
+    regex: string;
+}
+
+export interface CustomStats {
+    amount_by_name: Record<string, number>;
+    count_by_name: Record<string, number>;
+}
+
+/** A value in both fights, `change` goes from the first to the second. */
+export interface Delta {
+    change: number;
+    first: number;
+    second: number;
+}
+
+export interface DpsStats {
+    emit_by_enemy: Record<string, number>;
+    emit_by_kind: Record<string, number>;
+    emit_by_seconds: number[];
+    emit_by_seconds_absorbed: number[];
+    /** Damage done by each of your spells. */
+    emit_by_spell?: Record<string, number>;
+    /** Number of your hits, and of the critical ones. */
+    emit_count?: number;
+    emit_critical?: number;
+    received_by_enemy: Record<string, number>;
+    received_by_kind: Record<string, number>;
+    received_by_seconds: number[];
+    received_by_seconds_absorbed: number[];
+}
+
+/** A slice of the events of a fight, `total` counts all of them. */
+export interface EventPage {
+    events: RawEvent[];
+    offset: number;
+    total: number;
+}
+
+export type EventType = "damage" | "heal";
+
+export interface ExportedData {
+    custom_stats: CustomStats;
+    dps_stats: DpsStats;
+    errors: string[];
+    fights: Fight[];
+    food_stats: FoodStats;
+    heal_stats: HealStats;
+    resource_stats: ResourceStats;
+    schema_version: number;
+    unknown: UnknownEvent[];
+    unknown_shapes: Record<string, number>;
+    warnings: string[];
+}
+
+export interface Fight {
+    custom_stats: CustomStats;
+    dps_stats: DpsStats;
+    /** The first page of the hits and heals of the fight, only when a timeline is asked. */
+    events?: EventPage | null;
+    heal_stats: HealStats;
+    label?: string | null;
+    opponent: string[];
+    resource_stats: ResourceStats;
+    time: FightTimer;
+}
+
+/** How a fight did against another one, for "this attempt against the best one". */
+export interface FightComparison {
+    common_opponents: string[];
+    /** Your damage by spell, a spell missing from a fight counts 0 in it, as in the other tables. */
+    damage_by_spell: Record<string, Delta>;
+    damage_done: Delta;
+    damage_done_per_second: Delta;
+    damage_taken: Delta;
+    damage_taken_per_second: Delta;
+    duration: Delta;
+    /** Damage done by kind. */
+    emit_by_kind: Record<string, Delta>;
+    healing_by_spell: Record<string, Delta>;
+    healing_done: Delta;
+    healing_done_per_second: Delta;
+    healing_taken: Delta;
+    healing_taken_per_second: Delta;
+    /** Your spells used in the first fight and not in the second. */
+    only_in_first: string[];
+    /** Your spells used in the second fight and not in the first. */
+    only_in_second: string[];
+    /** Damage taken by kind. */
+    received_by_kind: Record<string, Delta>;
+}
+
+export interface FightTimer {
+    end: number;
+    start: number;
+}
+
+/** Which events a query keeps, every field left empty matches everything. */
+export interface Filter {
+    critical_only?: boolean;
+    emitter?: string | null;
+    end?: number | null;
+    kind?: string | null;
+    min_amount?: number;
+    receiver?: string | null;
+    spell?: string | null;
+    start?: number | null;
+}
+
+export interface FoodStats {
+    intervals: number[];
+    meals: Meal[];
+    total: number;
+}
+
+export interface HealStats {
+    emit_by_ally: Record<string, number>;
+    emit_by_seconds: number[];
+    emit_by_seconds_absorbed: number[];
+    /** Healing done by each of your spells. */
+    emit_by_spell?: Record<string, number>;
+    /** Number of your heals, and of the critical ones. */
+    emit_count?: number;
+    emit_critical?: number;
+    received_by_ally: Record<string, number>;
+    received_by_seconds: number[];
+    received_by_seconds_absorbed: number[];
+}
+
+export interface Meal {
+    date: number;
+    food: number;
+}
+
+/** Everything a parse can be told, each field left out keeps its default. */
+export interface ParseOptions {
+    /** Width in seconds of each item of the `*_by_seconds` arrays. */
+    bucket?: number;
+    /** The activities that start and keep a fight alive. */
+    fight_kinds?: ActivityKind[];
+    /** Fights shorter than this many seconds are dropped. */
+    minimum_time?: number;
+    patterns?: CustomPattern[];
+    /** Lines between two progress reports. */
+    progress_every?: number;
+    sections?: Sections;
+    /** Your character name, lines using it count as yours like the ones with `Your` and `You`. A single word, as the log starts a line with the first word of its emitter. */
+    self_name?: string | null;
+    strategy?: SplitStrategy;
+    /** Unknown lines are errors instead of warnings. */
+    strict?: boolean;
+    /** Seconds without activity that end a fight. */
+    time_between?: number;
+    /** Number of events given with each fight, none when left out. */
+    timeline?: number | null;
+    /** Minutes east of UTC used to write dates. */
+    timezone?: number;
+}
+
+/** How far a parse went, `bytes` are read from the start of the text. */
+export interface Progress {
+    bytes: number;
+    events: number;
+    lines: number;
+    total_bytes: number;
+}
+
+/** The trends of a character across sessions, changes go from the first session to the last. */
+export interface Progression {
+    character: string;
+    critical_rate_change: number;
+    damage_per_fight_change: number;
+    /** Unknown unless both sessions know their deaths. */
+    deaths_per_hour_change?: number | null;
+    healing_per_fight_change: number;
+    /** One point by session with fights, oldest first. */
+    sessions: SessionPoint[];
+    /** The spells whose share moved the most first. */
+    spells: SpellShift[];
+}
+
+export interface QueryResult {
+    dps_stats: DpsStats;
+    events: RawEvent[];
+    heal_stats: HealStats;
+}
+
+/** One hit or heal with its names resolved, `timestamp` is in milliseconds. */
+export interface RawEvent {
+    absorbed: number;
+    amount: number;
+    critical: boolean;
+    emitter: string;
+    kind: string;
+    receiver: string;
+    spell: string;
+    timestamp: number;
+    type: EventType;
+}
+
+export interface ResourceStats {
+    drained_by_kind: Record<string, number>;
+    restored_by_kind: Record<string, number>;
+}
+
+/** The parts of the export to compute, the ones left out stay empty. */
+export interface Sections {
+    custom?: boolean;
+    dps?: boolean;
+    fights?: boolean;
+    food?: boolean;
+    heal?: boolean;
+    resource?: boolean;
+    unknown?: boolean;
+}
+
+/** What a character did in one session, over its fights. */
+export interface SessionPoint {
+    /** Part of your hits and heals that were critical, from 0 to 1. */
+    critical_rate: number;
+    damage_per_fight: number;
+    /** Damage and healing over the time spent in fights. */
+    damage_per_second: number;
+    /** Unknown when the log was parsed without the `death` pattern. */
+    deaths?: number | null;
+    /** Deaths over the time from `start` to `end`, deaths also happen between fights. */
+    deaths_per_hour?: number | null;
+    end: number;
+    fights: number;
+    healing_per_fight: number;
+    healing_per_second: number;
+    /** Part of your damage and healing done by each spell, from 0 to 1. */
+    spell_share: Record<string, number>;
+    /** Start of the first fight and end of the last one, in seconds. */
+    start: number;
+}
+
+/** How much of the damage and healing of a character a spell did in the first and the last session. */
+export interface SpellShift {
+    change: number;
+    first: number;
+    last: number;
+    spell: string;
+}
+
+/** The strategies shipped with the parser, selected by `name` when deserialised. */
+export type SplitStrategy = { name: "gap" } | { name: "opponent"; window: number } | { length: number; name: "window" } | { markers: FightTimer[]; name: "manual" } | { bucket: number; name: "density"; threshold: number };
+
+export interface UnknownEvent {
+    date: number;
+    text: string;
+    tokens: string[];
+}
+
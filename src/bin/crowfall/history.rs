@@ -0,0 +1,49 @@
+use std::fs;
+
+use chrono::prelude::*;
+use chrono::Duration;
+use crowfall_parser::history::History;
+
+use crate::Command;
+
+fn character(command: &Command) -> Result<&str, String> {
+    command.character.as_deref().ok_or_else(|| "--character is needed".to_string())
+}
+
+/// Keep the fights of a log in the database, a log already imported changes nothing.
+pub fn import(command: &Command) -> Result<(), String> {
+    let contents = fs::read_to_string(&command.path).map_err(|error| format!("{}: {}", command.path.display(), error))?;
+    let mut history = History::open(&command.database)?;
+
+    let import = history.import(character(command)?, &contents, &command.options)?;
+    println!("{} new events, {} new fights", import.events, import.fights);
+
+    Ok(())
+}
+
+/// The fights of the last days and their average damage.
+pub fn history(command: &Command) -> Result<(), String> {
+    let character = character(command)?;
+    let history = History::open(&command.path)?;
+    let offset = command.options.offset()?;
+    let since = (Utc::now() - Duration::days(command.days)).timestamp();
+
+    let fights = history.fights(character, since, i64::MAX)?;
+    for fight in fights.iter() {
+        println!(
+            "{}  {:>5}s  {:>8} done  {:>8} taken  {}",
+            offset.timestamp(fight.start, 0).format("%Y-%m-%d %H:%M:%S"),
+            fight.end - fight.start,
+            fight.damage_done,
+            fight.damage_taken,
+            fight.opponents.join(", "),
+        );
+    }
+
+    match history.average_damage_per_fight(character, since)? {
+        Some(average) => println!("{} fights in {} days, {:.0} damage by fight", fights.len(), command.days, average),
+        None => println!("no fight in {} days", command.days),
+    }
+
+    Ok(())
+}
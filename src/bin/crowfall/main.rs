@@ -0,0 +1,223 @@
+//! Command line tools around the parser.
+//!
+//! `crowfall watch <file or directory>` follows a combat log while the game writes it
+//! and keeps a summary of the current and the last finished fight on screen.
+//!
+//! `crowfall serve <file or directory>`, built with the `server` feature, follows the log the same way,
+//! serves the web page and pushes the live fights to it over a WebSocket, on localhost only.
+//!
+//! `crowfall import <log>` and `crowfall history <database>`, built with the `storage` feature,
+//! keep parsed logs in a SQLite file and sum up the fights of a character.
+//!
+//! `crowfall report <log>` writes the parse as a single HTML file to share.
+
+#[cfg(feature = "storage")]
+mod history;
+#[cfg(feature = "server")]
+mod serve;
+
+use std::env;
+use std::fs;
+use std::path::PathBuf;
+use std::process;
+use std::thread;
+use std::time::Duration;
+
+use chrono::prelude::*;
+use crowfall_parser::options::ParseOptions;
+use crowfall_parser::session::{Live, Session};
+use crowfall_parser::tail::{Change, Tail};
+use crowfall_parser::report::html_report;
+use crowfall_parser::{parse_rust, Fight};
+
+const USAGE: &str = "usage: crowfall watch <file or directory> [--time-between SECONDS] [--minimum-time SECONDS] [--self-name NAME] [--timezone MINUTES] [--interval MILLISECONDS]
+       crowfall serve <file or directory> [--port PORT] [--static DIRECTORY] and the same flags as watch
+       crowfall import <log file> --character NAME [--database FILE] [--time-between SECONDS] [--minimum-time SECONDS] [--self-name NAME]
+       crowfall history <database> --character NAME [--days DAYS]
+       crowfall report <log file> [--output FILE] [--time-between SECONDS] [--minimum-time SECONDS] [--self-name NAME] [--timezone MINUTES]";
+
+struct Command {
+    name: String,
+    path: PathBuf,
+    options: ParseOptions,
+    interval: u64,
+    #[cfg_attr(not(feature = "server"), allow(dead_code))]
+    port: u16,
+    #[cfg_attr(not(feature = "server"), allow(dead_code))]
+    root: PathBuf,
+    #[cfg_attr(not(feature = "storage"), allow(dead_code))]
+    database: PathBuf,
+    #[cfg_attr(not(feature = "storage"), allow(dead_code))]
+    character: Option<String>,
+    #[cfg_attr(not(feature = "storage"), allow(dead_code))]
+    days: i64,
+    output: PathBuf,
+}
+
+fn value<T: std::str::FromStr>(flag: &str, value: Option<String>) -> Result<T, String> {
+    value
+        .and_then(|value| value.parse().ok())
+        .ok_or_else(|| format!("{} needs a value", flag))
+}
+
+fn arguments(mut args: impl Iterator<Item = String>) -> Result<Command, String> {
+    let name = args.next().ok_or_else(|| USAGE.to_string())?;
+
+    // a live fight shows up as soon as it starts, short ones are only hidden once over when asked
+    let mut options = ParseOptions::default();
+    if name == "watch" || name == "serve" {
+        options.minimum_time = 0;
+    }
+    let mut path = None;
+    let mut interval = 500;
+    let mut port = 8080;
+    let mut root = PathBuf::from("static");
+    let mut database = PathBuf::from("crowfall.sqlite");
+    let mut character = None;
+    let mut days = 30;
+    let mut output = PathBuf::from("report.html");
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--time-between" => options.time_between = value(&arg, args.next())?,
+            "--minimum-time" => options.minimum_time = value(&arg, args.next())?,
+            "--self-name" => options.self_name = Some(value(&arg, args.next())?),
+            "--timezone" => options.timezone = value(&arg, args.next())?,
+            "--interval" => interval = value(&arg, args.next())?,
+            "--port" => port = value(&arg, args.next())?,
+            "--static" => root = value(&arg, args.next())?,
+            "--database" => database = value(&arg, args.next())?,
+            "--character" => character = Some(value(&arg, args.next())?),
+            "--days" => days = value(&arg, args.next())?,
+            "--output" => output = value(&arg, args.next())?,
+            _ if path.is_none() && !arg.starts_with("--") => path = Some(PathBuf::from(arg)),
+            _ => return Err(USAGE.to_string()),
+        }
+    }
+    options.check()?;
+
+    Ok(Command {
+        name,
+        path: path.ok_or_else(|| USAGE.to_string())?,
+        options,
+        interval,
+        port,
+        root,
+        database,
+        character,
+        days,
+        output,
+    })
+}
+
+/// Read the log as it grows, `show` gets the fights after each read.
+fn follow(command: &Command, mut show: impl FnMut(&Live, &Tail)) -> Result<(), String> {
+    let mut tail = Tail::new(&command.path);
+    let mut session = Session::new(&command.options);
+
+    loop {
+        match tail.poll() {
+            Ok(Some(Change::Restart(text))) => {
+                session = Session::new(&command.options);
+                session.append(&text);
+                session.update();
+            }
+            Ok(Some(Change::Append(text))) => {
+                session.append(&text);
+                session.update();
+            }
+            Ok(None) => {}
+            Err(error) => return Err(format!("{}: {}", command.path.display(), error)),
+        }
+
+        show(&session.live(Utc::now().timestamp()), &tail);
+
+        thread::sleep(Duration::from_millis(command.interval));
+    }
+}
+
+fn per_second(amount: u64, duration: i64) -> u64 {
+    amount / duration.max(1) as u64
+}
+
+fn summary(title: &str, fight: Option<&Fight>, offset: &FixedOffset) -> String {
+    let fight = match fight {
+        Some(fight) => fight,
+        None => return format!("{}\n  none\n", title),
+    };
+
+    let time = |timestamp: i64| offset.timestamp(timestamp, 0).format("%H:%M:%S");
+    let duration = fight.duration();
+
+    format!(
+        "{} {} -> {} ({}s)\n  opponents     {}\n  damage done   {} ({}/s)\n  damage taken  {} ({}/s)\n  healing done  {} ({}/s)\n  healing taken {} ({}/s)\n",
+        title,
+        time(fight.time.start),
+        time(fight.time.end),
+        duration,
+        fight.opponent.join(", "),
+        fight.damage_done(),
+        per_second(fight.damage_done(), duration),
+        fight.damage_taken(),
+        per_second(fight.damage_taken(), duration),
+        fight.healing_done(),
+        per_second(fight.healing_done(), duration),
+        fight.healing_taken(),
+        per_second(fight.healing_taken(), duration),
+    )
+}
+
+fn watch(command: &Command) -> Result<(), String> {
+    let offset = command.options.offset()?;
+
+    follow(command, |live, tail| {
+        let file = tail.current().map(|file| file.display().to_string()).unwrap_or_default();
+
+        // clear the screen and write from the top left corner
+        print!(
+            "\x1b[2J\x1b[H{} - {} fights\n\n{}\n{}",
+            file,
+            live.fights,
+            summary("current fight", live.current, &offset),
+            summary("last fight", live.last, &offset),
+        );
+    })
+}
+
+fn report(command: &Command) -> Result<(), String> {
+    let contents = fs::read_to_string(&command.path).map_err(|error| format!("{}: {}", command.path.display(), error))?;
+    let data = parse_rust(&contents, &command.options);
+
+    fs::write(&command.output, html_report(&data, &command.options)?)
+        .map_err(|error| format!("{}: {}", command.output.display(), error))?;
+    println!("{} fights written to {}", data.fights.len(), command.output.display());
+
+    Ok(())
+}
+
+fn run(command: Command) -> Result<(), String> {
+    match command.name.as_str() {
+        "watch" => watch(&command),
+        "report" => report(&command),
+        #[cfg(feature = "server")]
+        "serve" => serve::serve(&command),
+        #[cfg(not(feature = "server"))]
+        "serve" => Err("serve needs the `server` feature, build with `--features server`".to_string()),
+        #[cfg(feature = "storage")]
+        "import" => history::import(&command),
+        #[cfg(feature = "storage")]
+        "history" => history::history(&command),
+        #[cfg(not(feature = "storage"))]
+        "import" | "history" => Err("import and history need the `storage` feature, build with `--features storage`".to_string()),
+        _ => Err(USAGE.to_string()),
+    }
+}
+
+fn main() {
+    let result = arguments(env::args().skip(1)).and_then(run);
+
+    if let Err(error) = result {
+        eprintln!("{}", error);
+        process::exit(1);
+    }
+}
@@ -0,0 +1,169 @@
+use std::fs;
+use std::io::{self, Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::path::{Component, Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+use tungstenite::{Message, WebSocket};
+
+use crate::{follow, Command};
+
+/// The path of the WebSocket, every message is a `Live` as JSON.
+const LIVE: &str = "/live";
+
+type Clients = Arc<Mutex<Vec<WebSocket<TcpStream>>>>;
+
+/// Serve `command.root` and push the live fights to `ws://127.0.0.1:<port>/live` whenever they change.
+pub fn serve(command: &Command) -> Result<(), String> {
+    // never reachable from another machine
+    let listener = TcpListener::bind(("127.0.0.1", command.port)).map_err(|error| error.to_string())?;
+    println!("serving {} on http://127.0.0.1:{}/", command.root.display(), command.port);
+
+    let clients: Clients = Arc::new(Mutex::new(vec![]));
+    let latest = Arc::new(Mutex::new(String::new()));
+
+    let (accepted, sent, root, port) = (clients.clone(), latest.clone(), command.root.clone(), command.port);
+    thread::spawn(move || {
+        for stream in listener.incoming().flatten() {
+            let (clients, latest, root) = (accepted.clone(), sent.clone(), root.clone());
+            thread::spawn(move || {
+                if let Err(error) = connection(stream, &root, port, &clients, &latest) {
+                    eprintln!("{}", error);
+                }
+            });
+        }
+    });
+
+    follow(command, |live, _| {
+        let message = serde_json::to_string(live).unwrap();
+        {
+            let mut latest = latest.lock().unwrap();
+            if *latest == message {
+                return;
+            }
+            *latest = message.clone();
+        }
+
+        // a client that cannot be written to anymore is gone
+        clients.lock().unwrap().retain_mut(|socket| socket.send(Message::Text(message.clone())).is_ok());
+    })
+}
+
+/// The request line and headers, left unread for the WebSocket handshake.
+fn peek_headers(stream: &TcpStream) -> io::Result<String> {
+    let mut buffer = [0; 8192];
+
+    loop {
+        let length = stream.peek(&mut buffer)?;
+        if length == 0 {
+            return Err(io::Error::new(io::ErrorKind::UnexpectedEof, "connection closed"));
+        }
+
+        if let Some(end) = buffer[..length].windows(4).position(|window| window == b"\r\n\r\n") {
+            return String::from_utf8(buffer[..end + 4].to_vec())
+                .map_err(|error| io::Error::new(io::ErrorKind::InvalidData, error));
+        }
+        if length == buffer.len() {
+            return Err(io::Error::new(io::ErrorKind::InvalidData, "headers too long"));
+        }
+
+        thread::sleep(Duration::from_millis(10));
+    }
+}
+
+fn header<'a>(headers: &'a str, name: &str) -> Option<&'a str> {
+    headers.lines()
+        .filter_map(|line| line.split_once(':'))
+        .find(|(key, _)| key.trim().eq_ignore_ascii_case(name))
+        .map(|(_, value)| value.trim())
+}
+
+/// Only requests naming this server are answered, a page of another site rebinding its name to 127.0.0.1 names its own.
+fn local_host(headers: &str, port: u16) -> bool {
+    match header(headers, "Host") {
+        Some(host) => host == format!("127.0.0.1:{}", port) || host.eq_ignore_ascii_case(&format!("localhost:{}", port)),
+        None => false,
+    }
+}
+
+/// Only the pages served here may open the WebSocket, not any site open in the same browser.
+fn local_origin(headers: &str, port: u16) -> bool {
+    match header(headers, "Origin") {
+        Some(origin) => origin == format!("http://127.0.0.1:{}", port) || origin == format!("http://localhost:{}", port),
+        None => true,
+    }
+}
+
+fn connection(mut stream: TcpStream, root: &Path, port: u16, clients: &Clients, latest: &Mutex<String>) -> Result<(), String> {
+    stream.set_read_timeout(Some(Duration::from_secs(5))).map_err(|error| error.to_string())?;
+    stream.set_write_timeout(Some(Duration::from_secs(5))).map_err(|error| error.to_string())?;
+
+    let headers = peek_headers(&stream).map_err(|error| error.to_string())?;
+    let target = headers.split(' ').nth(1).unwrap_or("/");
+    let path = target.split('?').next().unwrap_or("/");
+    let upgrade = header(&headers, "Upgrade").is_some_and(|value| value.eq_ignore_ascii_case("websocket"));
+
+    if !local_host(&headers, port) {
+        return respond(&mut stream, &headers, "403 Forbidden", "text/plain", b"forbidden");
+    }
+
+    if path == LIVE && upgrade {
+        if !local_origin(&headers, port) {
+            return respond(&mut stream, &headers, "403 Forbidden", "text/plain", b"forbidden");
+        }
+
+        let mut socket = tungstenite::accept(stream).map_err(|error| error.to_string())?;
+        // the clients stay locked, a change coming meanwhile is sent after this one, to this client too
+        let mut clients = clients.lock().unwrap();
+        let latest = latest.lock().unwrap().clone();
+        if !latest.is_empty() {
+            socket.send(Message::Text(latest)).map_err(|error| error.to_string())?;
+        }
+        clients.push(socket);
+        return Ok(());
+    }
+
+    match file(root, path) {
+        Some(file) => match fs::read(&file) {
+            Ok(body) => respond(&mut stream, &headers, "200 OK", content_type(&file), &body),
+            Err(_) => respond(&mut stream, &headers, "404 Not Found", "text/plain", b"not found"),
+        },
+        None => respond(&mut stream, &headers, "404 Not Found", "text/plain", b"not found"),
+    }
+}
+
+/// The file under `root` for a request path, nothing outside of it.
+fn file(root: &Path, path: &str) -> Option<PathBuf> {
+    let relative = Path::new(path.trim_start_matches('/'));
+    if relative.components().any(|component| !matches!(component, Component::Normal(_))) {
+        return None;
+    }
+
+    let file = root.join(relative);
+    Some(if file.is_dir() { file.join("index.html") } else { file })
+}
+
+fn content_type(file: &Path) -> &'static str {
+    match file.extension().and_then(|extension| extension.to_str()) {
+        Some("html") => "text/html; charset=utf-8",
+        Some("js") => "text/javascript",
+        Some("wasm") => "application/wasm",
+        Some("css") => "text/css",
+        Some("json") => "application/json",
+        Some("ts") | Some("txt") => "text/plain; charset=utf-8",
+        _ => "application/octet-stream",
+    }
+}
+
+fn respond(stream: &mut TcpStream, headers: &str, status: &str, content_type: &str, body: &[u8]) -> Result<(), String> {
+    // the request was only peeked, read it before answering
+    let mut request = vec![0; headers.len()];
+    let head = format!("HTTP/1.1 {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n", status, content_type, body.len());
+
+    stream.read_exact(&mut request)
+        .and_then(|_| stream.write_all(head.as_bytes()))
+        .and_then(|_| stream.write_all(body))
+        .map_err(|error| error.to_string())
+}
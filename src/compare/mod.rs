@@ -0,0 +1,124 @@
+use std::collections::{BTreeMap, HashMap};
+use serde::{Serialize, Deserialize};
+use schemars::JsonSchema;
+use crate::Fight;
+
+/// A value in both fights, `change` goes from the first to the second.
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone, Copy, PartialEq, Default)]
+pub struct Delta {
+    pub first: f64,
+    pub second: f64,
+    pub change: f64,
+}
+
+impl Delta {
+    fn new(first: f64, second: f64) -> Delta {
+        Delta { first, second, change: second - first }
+    }
+}
+
+/// How a fight did against another one, for "this attempt against the best one".
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone, PartialEq)]
+pub struct FightComparison {
+    pub duration: Delta,
+    pub damage_done: Delta,
+    pub damage_taken: Delta,
+    pub healing_done: Delta,
+    pub healing_taken: Delta,
+    pub damage_done_per_second: Delta,
+    pub damage_taken_per_second: Delta,
+    pub healing_done_per_second: Delta,
+    pub healing_taken_per_second: Delta,
+    /// Your damage by spell, a spell missing from a fight counts 0 in it, as in the other tables.
+    pub damage_by_spell: BTreeMap<String, Delta>,
+    pub healing_by_spell: BTreeMap<String, Delta>,
+    /// Damage done by kind.
+    pub emit_by_kind: BTreeMap<String, Delta>,
+    /// Damage taken by kind.
+    pub received_by_kind: BTreeMap<String, Delta>,
+    pub common_opponents: Vec<String>,
+    /// Your spells used in the first fight and not in the second.
+    pub only_in_first: Vec<String>,
+    /// Your spells used in the second fight and not in the first.
+    pub only_in_second: Vec<String>,
+}
+
+fn per_second(amount: u64, fight: &Fight) -> f64 {
+    amount as f64 / fight.duration().max(1) as f64
+}
+
+fn by_name(first: &HashMap<String, u32>, second: &HashMap<String, u32>) -> BTreeMap<String, Delta> {
+    first.keys().chain(second.keys())
+        .map(|name| {
+            let amount = |by_name: &HashMap<String, u32>| by_name.get(name).copied().unwrap_or(0) as f64;
+            (name.clone(), Delta::new(amount(first), amount(second)))
+        })
+        .collect()
+}
+
+fn spells(fight: &Fight) -> Vec<&String> {
+    let mut spells: Vec<&String> = fight.dps_stats.emit_by_spell.keys().chain(fight.heal_stats.emit_by_spell.keys()).collect();
+    spells.sort();
+    spells.dedup();
+    spells
+}
+
+fn missing(from: &[&String], other: &[&String]) -> Vec<String> {
+    from.iter().filter(|spell| !other.contains(spell)).map(|spell| spell.to_string()).collect()
+}
+
+pub fn compare(first: &Fight, second: &Fight) -> FightComparison {
+    let total = |value: fn(&Fight) -> u64| Delta::new(value(first) as f64, value(second) as f64);
+    let rate = |value: fn(&Fight) -> u64| Delta::new(per_second(value(first), first), per_second(value(second), second));
+    let (first_spells, second_spells) = (spells(first), spells(second));
+
+    FightComparison {
+        duration: Delta::new(first.duration() as f64, second.duration() as f64),
+        damage_done: total(Fight::damage_done),
+        damage_taken: total(Fight::damage_taken),
+        healing_done: total(Fight::healing_done),
+        healing_taken: total(Fight::healing_taken),
+        damage_done_per_second: rate(Fight::damage_done),
+        damage_taken_per_second: rate(Fight::damage_taken),
+        healing_done_per_second: rate(Fight::healing_done),
+        healing_taken_per_second: rate(Fight::healing_taken),
+        damage_by_spell: by_name(&first.dps_stats.emit_by_spell, &second.dps_stats.emit_by_spell),
+        healing_by_spell: by_name(&first.heal_stats.emit_by_spell, &second.heal_stats.emit_by_spell),
+        emit_by_kind: by_name(&first.dps_stats.emit_by_kind, &second.dps_stats.emit_by_kind),
+        received_by_kind: by_name(&first.dps_stats.received_by_kind, &second.dps_stats.received_by_kind),
+        common_opponents: first.opponent.iter().filter(|name| second.opponent.contains(name)).cloned().collect(),
+        only_in_first: missing(&first_spells, &second_spells),
+        only_in_second: missing(&second_spells, &first_spells),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    // Note this useful idiom: importing names from outer (for mod tests) scope.
+    use super::*;
+    use crate::options::ParseOptions;
+    use crate::parse_rust;
+
+    const LOG: &str = "2021-03-31T04:35:00.000Z INFO    COMBAT    - Combat _||_ Event=[Your Hammer hit Cleric for 300 Crush damage.]
+2021-03-31T04:35:10.000Z INFO    COMBAT    - Combat _||_ Event=[Your Hammer hit Cleric for 100 Crush damage.]
+2021-03-31T04:35:10.000Z INFO    COMBAT    - Combat _||_ Event=[Cleric Smite hit You for 50 Holy damage.]
+2021-03-31T04:37:00.000Z INFO    COMBAT    - Combat _||_ Event=[Your Static Bolt hit Cleric for 500 Nature damage.]
+2021-03-31T04:37:05.000Z INFO    COMBAT    - Combat _||_ Event=[Your Hammer hit Ranger for 100 Crush damage.]";
+
+    #[test]
+    fn assert_compare() {
+        let calc = parse_rust(LOG, &ParseOptions { minimum_time: 0, ..Default::default() });
+        let comparison = compare(&calc.fights[0], &calc.fights[1]);
+
+        assert_eq!(comparison.duration, Delta { first: 10.0, second: 5.0, change: -5.0 });
+        assert_eq!(comparison.damage_done.change, 200.0);
+        assert_eq!(comparison.damage_done_per_second, Delta { first: 40.0, second: 120.0, change: 80.0 });
+        assert_eq!(comparison.damage_taken.change, -50.0);
+        assert_eq!(comparison.damage_by_spell["Hammer"].change, -300.0);
+        assert_eq!(comparison.damage_by_spell["Static Bolt"].first, 0.0);
+        assert_eq!(comparison.received_by_kind["Holy"].second, 0.0);
+        assert_eq!(comparison.common_opponents, vec!["cleric".to_string()]);
+        assert!(comparison.only_in_first.is_empty());
+        assert_eq!(comparison.only_in_second, vec!["Static Bolt".to_string()]);
+    }
+}
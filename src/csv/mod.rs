@@ -0,0 +1,164 @@
+use chrono::prelude::*;
+use std::collections::HashMap;
+use crate::query::{EventType, RawEvent};
+use crate::Fight;
+
+/// Quote a field when a spreadsheet would split it.
+fn field(value: &str) -> String {
+    if value.contains(',') || value.contains('"') || value.contains('\n') {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+fn row(fields: &[String]) -> String {
+    let fields: Vec<String> = fields.iter().map(|value| field(value)).collect();
+    fields.join(",") + "\n"
+}
+
+fn event_type(event_type: EventType) -> &'static str {
+    match event_type {
+        EventType::Damage => "damage",
+        EventType::Heal => "heal",
+    }
+}
+
+fn date(timestamp_millis: i64, offset: &FixedOffset) -> Result<String, String> {
+    offset.timestamp_millis_opt(timestamp_millis).single()
+        .map(|date| date.to_rfc3339_opts(SecondsFormat::Millis, true))
+        .ok_or_else(|| format!("{} is not a date", timestamp_millis))
+}
+
+/// One line per hit or heal, dated to the millisecond in `offset`, an error for a date out of range.
+pub fn events_csv(events: &[RawEvent], offset: &FixedOffset) -> Result<String, String> {
+    let mut csv = "date,type,emitter,spell,receiver,amount,absorbed,kind,critical\n".to_string();
+
+    for event in events.iter() {
+        csv += &row(&[
+            date(event.timestamp, offset)?,
+            event_type(event.event_type).to_string(),
+            event.emitter.to_string(),
+            event.spell.to_string(),
+            event.receiver.to_string(),
+            event.amount.to_string(),
+            event.absorbed.to_string(),
+            event.kind.to_string(),
+            event.critical.to_string(),
+        ]);
+    }
+
+    Ok(csv)
+}
+
+/// One line per fight, damage and healing count the absorbed part too.
+pub fn fights_csv(fights: &[Fight], offset: &FixedOffset) -> Result<String, String> {
+    let mut csv = "fight,label,start,end,duration,damage_done,damage_taken,healing_done,healing_taken,opponents\n".to_string();
+
+    for (index, fight) in fights.iter().enumerate() {
+        csv += &row(&[
+            index.to_string(),
+            fight.label.clone().unwrap_or_default(),
+            date(fight.time.start.saturating_mul(1000), offset)?,
+            date(fight.time.end.saturating_mul(1000), offset)?,
+            fight.duration().to_string(),
+            fight.damage_done().to_string(),
+            fight.damage_taken().to_string(),
+            fight.healing_done().to_string(),
+            fight.healing_taken().to_string(),
+            fight.opponent.join("; "),
+        ]);
+    }
+
+    Ok(csv)
+}
+
+#[derive(Default)]
+struct SpellLine {
+    hits: u32,
+    amount: u64,
+    absorbed: u64,
+    critical: u32,
+    max: u32,
+}
+
+/// One line per spell of each emitter, the biggest total first.
+pub fn spells_csv(events: &[RawEvent]) -> String {
+    let mut by_spell: HashMap<(&str, &str, &str), SpellLine> = HashMap::new();
+
+    for event in events.iter() {
+        let line = by_spell.entry((event_type(event.event_type), &event.emitter, &event.spell)).or_default();
+        line.hits += 1;
+        line.amount += event.amount as u64;
+        line.absorbed += event.absorbed as u64;
+        line.critical += event.critical as u32;
+        line.max = line.max.max(event.amount);
+    }
+
+    let mut lines: Vec<_> = by_spell.into_iter().collect();
+    lines.sort_by(|(a_key, a), (b_key, b)| b.amount.cmp(&a.amount).then(a_key.cmp(b_key)));
+
+    let mut csv = "type,emitter,spell,hits,amount,absorbed,critical,max\n".to_string();
+    for ((event_type, emitter, spell), line) in lines.iter() {
+        csv += &row(&[
+            event_type.to_string(),
+            emitter.to_string(),
+            spell.to_string(),
+            line.hits.to_string(),
+            line.amount.to_string(),
+            line.absorbed.to_string(),
+            line.critical.to_string(),
+            line.max.to_string(),
+        ]);
+    }
+
+    csv
+}
+
+#[cfg(test)]
+mod tests {
+    // Note this useful idiom: importing names from outer (for mod tests) scope.
+    use super::*;
+
+    fn event(timestamp: i64, spell: &str, amount: u32, critical: bool) -> RawEvent {
+        RawEvent {
+            timestamp,
+            event_type: EventType::Damage,
+            emitter: "Your".to_string(),
+            spell: spell.to_string(),
+            receiver: "Ranger, the \"Defender\"".to_string(),
+            amount,
+            absorbed: 0,
+            kind: "Fire".to_string(),
+            critical,
+        }
+    }
+
+    #[test]
+    fn assert_events_csv() {
+        let csv = events_csv(&[event(1616013645111, "Static Bolt", 100, true)], &FixedOffset::east(0)).unwrap();
+        let lines: Vec<&str> = csv.lines().collect();
+
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[1], "2021-03-17T20:40:45.111Z,damage,Your,Static Bolt,\"Ranger, the \"\"Defender\"\"\",100,0,Fire,true");
+
+        let csv = events_csv(&[event(1616013645111, "Static Bolt", 100, true)], &FixedOffset::east(7200)).unwrap();
+        assert!(csv.lines().nth(1).unwrap().starts_with("2021-03-17T22:40:45.111+02:00,"));
+
+        assert!(events_csv(&[event(i64::MAX, "Static Bolt", 100, true)], &FixedOffset::east(0)).is_err());
+    }
+
+    #[test]
+    fn assert_spells_csv() {
+        let csv = spells_csv(&[
+            event(1616013645111, "Static Bolt", 100, true),
+            event(1616013646111, "Static Bolt", 40, false),
+            event(1616013647111, "Hammer", 300, false),
+        ]);
+        let lines: Vec<&str> = csv.lines().collect();
+
+        assert_eq!(lines.len(), 3);
+        assert_eq!(lines[1], "damage,Your,Hammer,1,300,0,0,300");
+        assert_eq!(lines[2], "damage,Your,Static Bolt,2,140,0,1,100");
+    }
+}
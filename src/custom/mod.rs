@@ -0,0 +1,182 @@
+use regex::Regex;
+
+use chrono::prelude::*;
+use std::collections::HashMap;
+use serde::{Serialize, Deserialize};
+use schemars::JsonSchema;
+
+/// A user supplied grammar, the regex can use the named captures
+/// `emitter`, `spell`, `receiver`, `amount` and `kind`, all of them optional.
+#[derive(Debug, PartialEq, Serialize, Deserialize, JsonSchema, Clone)]
+pub struct CustomPattern {
+    pub name: String,
+    pub regex: String,
+}
+
+#[derive(Debug)]
+pub struct CompiledPattern {
+    pub name: String,
+    pub regex: Regex,
+}
+
+pub fn compile_patterns(patterns: &[CustomPattern]) -> (Vec<CompiledPattern>, Vec<String>) {
+    let mut compiled = vec![];
+    let mut errors = vec![];
+
+    for pattern in patterns.iter() {
+        match Regex::new(&pattern.regex) {
+            Ok(regex) => compiled.push(CompiledPattern {
+                name: pattern.name.to_string(),
+                regex,
+            }),
+            Err(e) => errors.push(format!("invalid pattern {} : {}", pattern.name, e)),
+        }
+    }
+
+    (compiled, errors)
+}
+
+#[derive(Debug, PartialEq)]
+pub struct CustomEvent {
+    pub date: DateTime<FixedOffset>,
+    pub name: String,
+    pub emitter: String,
+    pub spell: String,
+    pub receiver: String,
+    pub amount: u32,
+    pub kind: String,
+}
+
+pub fn parse_custom(patterns: &[CompiledPattern], row: &str, dt: DateTime<FixedOffset>) -> Option<CustomEvent> {
+    for pattern in patterns.iter() {
+        let cap = match pattern.regex.captures(row) {
+            Some(cap) => cap,
+            None => continue,
+        };
+
+        let field = |name: &str| cap.name(name).map(|m| m.as_str().to_string()).unwrap_or_default();
+
+        return Some(CustomEvent {
+            date: dt,
+            name: pattern.name.to_string(),
+            emitter: field("emitter"),
+            spell: field("spell"),
+            receiver: field("receiver"),
+            amount: cap.name("amount").and_then(|m| m.as_str().parse::<u32>().ok()).unwrap_or(0),
+            kind: field("kind"),
+        });
+    }
+
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    // Note this useful idiom: importing names from outer (for mod tests) scope.
+    use super::*;
+
+    fn stun_pattern() -> Vec<CompiledPattern> {
+        compile_patterns(&[CustomPattern {
+            name: "stun".to_string(),
+            regex: "^(?P<emitter>[^ ]+) (?P<spell>.+) stunned (?P<receiver>.+) for (?P<amount>[0-9]+) seconds.$".to_string(),
+        }]).0
+    }
+
+    #[test]
+    fn assert_custom_named_captures() {
+        let tt = "Your Shield Bash stunned Ranger Defender for 3 seconds.";
+        let dt = DateTime::from(Utc::now());
+        assert_eq!(
+            parse_custom(&stun_pattern(), tt, dt).unwrap(),
+            CustomEvent {
+                date: dt,
+                name: "stun".to_string(),
+                emitter: "Your".to_string(),
+                spell: "Shield Bash".to_string(),
+                receiver: "Ranger Defender".to_string(),
+                amount: 3,
+                kind: "".to_string(),
+            }
+        )
+    }
+
+    #[test]
+    fn assert_custom_no_match() {
+        let tt = "Your Static Bolt hit Cleric Defender for 100 Nature damage.";
+        let dt = DateTime::from(Utc::now());
+        assert_eq!(parse_custom(&stun_pattern(), tt, dt), None)
+    }
+
+    #[test]
+    fn assert_custom_invalid_regex() {
+        let (compiled, errors) = compile_patterns(&[CustomPattern {
+            name: "broken".to_string(),
+            regex: "(unclosed".to_string(),
+        }]);
+        assert_eq!(compiled.len(), 0);
+        assert_eq!(errors.len(), 1);
+    }
+}
+
+#[derive(Debug, PartialEq, Clone, Default)]
+#[derive(Serialize, Deserialize, JsonSchema)]
+pub struct CustomStats {
+    pub count_by_name: HashMap<String, u32>,
+    pub amount_by_name: HashMap<String, u32>,
+}
+
+pub fn stats_custom(list: &[CustomEvent], start: Option<i64>, end: Option<i64>) -> CustomStats {
+    let mut count_by_name = HashMap::new();
+    let mut amount_by_name = HashMap::new();
+
+    for custom in list.iter() {
+        if custom.date.timestamp() < start.unwrap_or(0) || custom.date.timestamp() > end.unwrap_or(i64::MAX) {
+            continue;
+        }
+
+        *count_by_name.entry(custom.name.to_string()).or_insert(0) += 1;
+        *amount_by_name.entry(custom.name.to_string()).or_insert(0) += custom.amount;
+    }
+
+    CustomStats {
+        count_by_name,
+        amount_by_name,
+    }
+}
+
+#[cfg(test)]
+mod stats_tests {
+    // Note this useful idiom: importing names from outer (for mod tests) scope.
+    use super::*;
+
+    #[test]
+    fn assert_custom_count_by_time() {
+        let event = |date: &str, name: &str, amount: u32| CustomEvent {
+            date: DateTime::parse_from_rfc3339(date).unwrap(),
+            name: name.to_string(),
+            emitter: "Your".to_string(),
+            spell: "Shield Bash".to_string(),
+            receiver: "Paul".to_string(),
+            amount,
+            kind: "".to_string(),
+        };
+        let list = vec![
+            event("2021-03-17T20:20:45.111Z", "stun", 3),
+            event("2021-03-17T20:40:45.111Z", "stun", 2),
+            event("2021-03-17T20:40:50.111Z", "stun", 4),
+            event("2021-03-17T20:41:45.111Z", "knockdown", 0),
+        ];
+
+        let stats = stats_custom(
+            &list,
+            Some(DateTime::parse_from_rfc3339("2021-03-17T20:40:00.111Z").unwrap().timestamp()),
+            Some(DateTime::parse_from_rfc3339("2021-03-17T20:42:00.111Z").unwrap().timestamp()),
+        );
+
+        let mut count: HashMap<String, u32> = HashMap::new();
+        count.insert("stun".to_string(), 2);
+        count.insert("knockdown".to_string(), 1);
+        assert_eq!(stats.count_by_name, count);
+        assert_eq!(stats.amount_by_name["stun"], 6);
+    }
+}
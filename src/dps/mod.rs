@@ -1,37 +1,59 @@
+#[cfg(test)]
 use regex::Regex;
 
 use chrono::prelude::*;
 use std::collections::HashMap;
 use serde::{Serialize, Deserialize};
+use schemars::JsonSchema;
 use std::iter::FromIterator;
+use crate::intern::{Interner, Symbol};
 
 const SELF_EMITTER: &str = "Your";
 const SELF_RECEIVER: &str = "You";
 
+#[cfg(test)]
 lazy_static! {
     pub static ref RE_DPS: Regex = Regex::new("^([^ ]+) ?(.+)? hit (.+) for ([0-9]+) ?(\\(([0-9]+) absorbed\\))? ?(([^\\(]+) damage)? ?(\\(Critical\\))?.$").unwrap();
 }
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, PartialEq, Clone)]
 pub struct Dps {
     pub date: DateTime<FixedOffset>,
-    pub emitter: String,
-    pub spell: String,
-    pub receiver: String,
+    pub emitter: Symbol,
+    pub spell: Symbol,
+    pub receiver: Symbol,
     pub damage: u32,
-    pub kind: String,
+    pub kind: Symbol,
     pub absorbed: u32,
     pub critical: bool,
 }
 
-pub fn parse_dps(row: &str, dt: DateTime<FixedOffset>) -> Option<Dps> {
+impl Dps {
+    /// The other side of a hit involving you, if any.
+    pub fn opponent(&self, names: &Interner) -> Option<Symbol> {
+        let self_emitter = names.get(SELF_EMITTER);
+        let self_receiver = names.get(SELF_RECEIVER);
+
+        if Some(self.emitter) == self_emitter && Some(self.receiver) != self_receiver {
+            return Some(self.receiver);
+        }
+        if Some(self.receiver) == self_receiver && Some(self.emitter) != self_emitter {
+            return Some(self.emitter);
+        }
+
+        None
+    }
+}
+
+#[cfg(test)]
+pub fn parse_dps(row: &str, dt: DateTime<FixedOffset>, names: &mut Interner) -> Option<Dps> {
     for cap in RE_DPS.captures_iter(row) {
         let kind = match cap.get(8) {
             Some(_) => {
-                cap[8].to_string()
+                names.intern(&cap[8])
             }
             None => {
-                "".to_string()
+                Symbol::default()
             }
         };
 
@@ -55,18 +77,18 @@ pub fn parse_dps(row: &str, dt: DateTime<FixedOffset>) -> Option<Dps> {
 
         let spell = match cap.get(2) {
             Some(_) => {
-                cap[2].to_string()
+                names.intern(&cap[2])
             }
             None => {
-                "".to_string()
+                Symbol::default()
             }
         };
 
         return Some(Dps {
             date: dt,
-            emitter: cap[1].to_string(),
+            emitter: names.intern(&cap[1]),
             spell,
-            receiver: cap[3].to_string(),
+            receiver: names.intern(&cap[3]),
             damage: cap[4].parse::<u32>().unwrap(),
             kind,
             absorbed,
@@ -86,16 +108,17 @@ mod parse_tests {
     fn assert_dps_simple() {
         let tt = "Your Freezing Storm hit Major Thrall of Dark for 101 Ice damage.";
         let dt = DateTime::from(Utc::now());
+        let mut names = Interner::new();
         assert!(RE_DPS.is_match(tt));
         assert_eq!(
-            parse_dps(tt, dt).unwrap(),
+            parse_dps(tt, dt, &mut names).unwrap(),
             Dps {
                 date: dt,
-                emitter: "Your".to_string(),
-                spell: "Freezing Storm".to_string(),
-                receiver: "Major Thrall of Dark".to_string(),
+                emitter: names.intern("Your"),
+                spell: names.intern("Freezing Storm"),
+                receiver: names.intern("Major Thrall of Dark"),
                 damage: 101,
-                kind: "Ice".to_string(),
+                kind: names.intern("Ice"),
                 absorbed: 0,
                 critical: false,
             }
@@ -106,16 +129,17 @@ mod parse_tests {
     fn assert_dps_full_absorbed() {
         let tt = "Your Shatter Storm hit RexAlchy for 0 (51 absorbed).";
         let dt = DateTime::from(Utc::now());
+        let mut names = Interner::new();
         assert!(RE_DPS.is_match(tt));
         assert_eq!(
-            parse_dps(tt, dt).unwrap(),
+            parse_dps(tt, dt, &mut names).unwrap(),
             Dps {
                 date: dt,
-                emitter: "Your".to_string(),
-                spell: "Shatter Storm".to_string(),
-                receiver: "RexAlchy".to_string(),
+                emitter: names.intern("Your"),
+                spell: names.intern("Shatter Storm"),
+                receiver: names.intern("RexAlchy"),
                 damage: 0,
-                kind: "".to_string(),
+                kind: names.intern(""),
                 absorbed: 51,
                 critical: false,
             }
@@ -126,16 +150,17 @@ mod parse_tests {
     fn assert_dps_partially_absorbed() {
         let tt = "Your Spiral Cast hit Thrall Soul for 272 (12 absorbed) Ice damage.";
         let dt = DateTime::from(Utc::now());
+        let mut names = Interner::new();
         assert!(RE_DPS.is_match(tt));
         assert_eq!(
-            parse_dps(tt, dt).unwrap(),
+            parse_dps(tt, dt, &mut names).unwrap(),
             Dps {
                 date: dt,
-                emitter: "Your".to_string(),
-                spell: "Spiral Cast".to_string(),
-                receiver: "Thrall Soul".to_string(),
+                emitter: names.intern("Your"),
+                spell: names.intern("Spiral Cast"),
+                receiver: names.intern("Thrall Soul"),
                 damage: 272,
-                kind: "Ice".to_string(),
+                kind: names.intern("Ice"),
                 absorbed: 12,
                 critical: false,
             }
@@ -146,16 +171,17 @@ mod parse_tests {
     fn assert_dps_critical() {
         let tt = "Your Coalesce Forestry hit Urgu Myrmidon Chief for 311 Nature damage (Critical).";
         let dt = DateTime::from(Utc::now());
+        let mut names = Interner::new();
         assert!(RE_DPS.is_match(tt));
         assert_eq!(
-            parse_dps(tt, dt).unwrap(),
+            parse_dps(tt, dt, &mut names).unwrap(),
             Dps {
                 date: dt,
-                emitter: "Your".to_string(),
-                spell: "Coalesce Forestry".to_string(),
-                receiver: "Urgu Myrmidon Chief".to_string(),
+                emitter: names.intern("Your"),
+                spell: names.intern("Coalesce Forestry"),
+                receiver: names.intern("Urgu Myrmidon Chief"),
                 damage: 311,
-                kind: "Nature".to_string(),
+                kind: names.intern("Nature"),
                 absorbed: 0,
                 critical: true,
             }
@@ -166,16 +192,17 @@ mod parse_tests {
     fn assert_dps_partially_absorbed_critical() {
         let tt = "Your Retaliate hit UDeadPRO for 292 (233 absorbed) Nature damage (Critical).";
         let dt = DateTime::from(Utc::now());
+        let mut names = Interner::new();
         assert!(RE_DPS.is_match(tt));
         assert_eq!(
-            parse_dps(tt, dt).unwrap(),
+            parse_dps(tt, dt, &mut names).unwrap(),
             Dps {
                 date: dt,
-                emitter: "Your".to_string(),
-                spell: "Retaliate".to_string(),
-                receiver: "UDeadPRO".to_string(),
+                emitter: names.intern("Your"),
+                spell: names.intern("Retaliate"),
+                receiver: names.intern("UDeadPRO"),
                 damage: 292,
-                kind: "Nature".to_string(),
+                kind: names.intern("Nature"),
                 absorbed: 233,
                 critical: true,
             }
@@ -186,16 +213,17 @@ mod parse_tests {
     fn assert_dps_none() {
         let tt = "Your Holy Symbol hit Zankara for 0 (Critical).";
         let dt = DateTime::from(Utc::now());
+        let mut names = Interner::new();
         assert!(RE_DPS.is_match(tt));
         assert_eq!(
-            parse_dps(tt, dt).unwrap(),
+            parse_dps(tt, dt, &mut names).unwrap(),
             Dps {
                 date: dt,
-                emitter: "Your".to_string(),
-                spell: "Holy Symbol".to_string(),
-                receiver: "Zankara".to_string(),
+                emitter: names.intern("Your"),
+                spell: names.intern("Holy Symbol"),
+                receiver: names.intern("Zankara"),
                 damage: 0,
-                kind: "".to_string(),
+                kind: names.intern(""),
                 absorbed: 0,
                 critical: true,
             }
@@ -206,16 +234,17 @@ mod parse_tests {
     fn assert_hit_none_critical() {
         let tt = "Gamako Fervor hit You for 0 (Critical).";
         let dt = DateTime::from(Utc::now());
+        let mut names = Interner::new();
         assert!(RE_DPS.is_match(tt));
         assert_eq!(
-            parse_dps(tt, dt).unwrap(),
+            parse_dps(tt, dt, &mut names).unwrap(),
             Dps {
                 date: dt,
-                emitter: "Gamako".to_string(),
-                spell: "Fervor".to_string(),
-                receiver: "You".to_string(),
+                emitter: names.intern("Gamako"),
+                spell: names.intern("Fervor"),
+                receiver: names.intern("You"),
                 damage: 0,
-                kind: "".to_string(),
+                kind: names.intern(""),
                 absorbed: 0,
                 critical: true,
             }
@@ -226,16 +255,17 @@ mod parse_tests {
     fn assert_fire_dps() {
         let tt = "Sun Elf Confessor Fire Aura hit You for 26 Fire damage.";
         let dt = DateTime::from(Utc::now());
+        let mut names = Interner::new();
         assert!(RE_DPS.is_match(tt));
         assert_eq!(
-            parse_dps(tt, dt).unwrap(),
+            parse_dps(tt, dt, &mut names).unwrap(),
             Dps {
                 date: dt,
-                emitter: "Sun".to_string(),
-                spell: "Elf Confessor Fire Aura".to_string(),
-                receiver: "You".to_string(),
+                emitter: names.intern("Sun"),
+                spell: names.intern("Elf Confessor Fire Aura"),
+                receiver: names.intern("You"),
                 damage: 26,
-                kind: "Fire".to_string(),
+                kind: names.intern("Fire"),
                 absorbed: 0,
                 critical: false,
             }
@@ -246,16 +276,17 @@ mod parse_tests {
     fn assert_no_spell() {
         let tt = "Swoop hit You for 46 Piercing damage.";
         let dt = DateTime::from(Utc::now());
+        let mut names = Interner::new();
         assert!(RE_DPS.is_match(tt));
         assert_eq!(
-            parse_dps(tt, dt).unwrap(),
+            parse_dps(tt, dt, &mut names).unwrap(),
             Dps {
                 date: dt,
-                emitter: "Swoop".to_string(),
-                spell: "".to_string(),
-                receiver: "You".to_string(),
+                emitter: names.intern("Swoop"),
+                spell: names.intern(""),
+                receiver: names.intern("You"),
                 damage: 46,
-                kind: "Piercing".to_string(),
+                kind: names.intern("Piercing"),
                 absorbed: 0,
                 critical: false,
             }
@@ -266,16 +297,17 @@ mod parse_tests {
     fn assert_partially_absorbed() {
         let tt = "Urgu Myrmidon Chief Slash hit You for 206 (198 absorbed) Crushing damage.";
         let dt = DateTime::from(Utc::now());
+        let mut names = Interner::new();
         assert!(RE_DPS.is_match(tt));
         assert_eq!(
-            parse_dps(tt, dt).unwrap(),
+            parse_dps(tt, dt, &mut names).unwrap(),
             Dps {
                 date: dt,
-                emitter: "Urgu".to_string(),
-                spell: "Myrmidon Chief Slash".to_string(),
-                receiver: "You".to_string(),
+                emitter: names.intern("Urgu"),
+                spell: names.intern("Myrmidon Chief Slash"),
+                receiver: names.intern("You"),
                 damage: 206,
-                kind: "Crushing".to_string(),
+                kind: names.intern("Crushing"),
                 absorbed: 198,
                 critical: false,
             }
@@ -283,8 +315,8 @@ mod parse_tests {
     }
 }
 
-#[derive(Debug, PartialEq)]
-#[derive(Serialize, Deserialize)]
+#[derive(Debug, PartialEq, Clone, Default)]
+#[derive(Serialize, Deserialize, JsonSchema)]
 pub struct DpsStats {
     pub received_by_kind: HashMap<String, u32>,
     pub emit_by_kind: HashMap<String, u32>,
@@ -294,9 +326,17 @@ pub struct DpsStats {
     pub emit_by_seconds_absorbed: Vec<u32>,
     pub received_by_seconds: Vec<u32>,
     pub received_by_seconds_absorbed: Vec<u32>,
+    /// Damage done by each of your spells.
+    #[serde(default)]
+    pub emit_by_spell: HashMap<String, u32>,
+    /// Number of your hits, and of the critical ones.
+    #[serde(default)]
+    pub emit_count: u32,
+    #[serde(default)]
+    pub emit_critical: u32,
 }
 
-pub fn stats_dps(list: &Vec<Dps>, start: Option<i64>, end: Option<i64>) -> (DpsStats, Vec<String>) {
+pub fn stats_dps(list: &[Dps], names: &Interner, start: Option<i64>, end: Option<i64>) -> (DpsStats, Vec<String>) {
     let mut received_by_kind = HashMap::new();
     let mut emit_by_kind = HashMap::new();
     let mut received_by_enemy = HashMap::new();
@@ -305,8 +345,14 @@ pub fn stats_dps(list: &Vec<Dps>, start: Option<i64>, end: Option<i64>) -> (DpsS
     let mut emit_by_seconds_absorbed = vec![];
     let mut received_by_seconds = vec![];
     let mut received_by_seconds_absorbed = vec![];
+    let mut emit_by_spell = HashMap::new();
+    let mut emit_count = 0;
+    let mut emit_critical = 0;
     let mut take_seconds = false;
 
+    let self_emitter = names.get(SELF_EMITTER);
+    let self_receiver = names.get(SELF_RECEIVER);
+
     if start != None && end != None {
         let s = (end.unwrap() - start.unwrap() +1) as usize ;
 
@@ -322,20 +368,20 @@ pub fn stats_dps(list: &Vec<Dps>, start: Option<i64>, end: Option<i64>) -> (DpsS
             continue;
         }
 
-        if dps.kind != "" {
-            if dps.receiver == SELF_RECEIVER {
-                let rec = received_by_kind.entry(dps.kind.to_string()).or_insert(0);
+        if dps.kind != Symbol::default() {
+            if Some(dps.receiver) == self_receiver {
+                let rec = received_by_kind.entry(dps.kind).or_insert(0);
                 *rec += dps.damage + dps.absorbed;
             }
 
-            if dps.emitter == SELF_EMITTER {
-                let emit = emit_by_kind.entry(dps.kind.to_string()).or_insert(0);
+            if Some(dps.emitter) == self_emitter {
+                let emit = emit_by_kind.entry(dps.kind).or_insert(0);
                 *emit += dps.damage + dps.absorbed;
             }
         }
 
-        if dps.receiver == SELF_RECEIVER {
-            let rec = received_by_enemy.entry(dps.emitter.to_string()).or_insert(0);
+        if Some(dps.receiver) == self_receiver {
+            let rec = received_by_enemy.entry(dps.emitter).or_insert(0);
             *rec += dps.damage + dps.absorbed;
             if take_seconds {
                 received_by_seconds[(dps.date.timestamp() - start.unwrap()) as usize] += dps.damage;
@@ -343,8 +389,12 @@ pub fn stats_dps(list: &Vec<Dps>, start: Option<i64>, end: Option<i64>) -> (DpsS
             }
         }
 
-        if dps.emitter == SELF_EMITTER {
-            let emit = emit_by_enemy.entry(dps.receiver.to_string()).or_insert(0);
+        if Some(dps.emitter) == self_emitter {
+            *emit_by_spell.entry(dps.spell).or_insert(0) += dps.damage + dps.absorbed;
+            emit_count += 1;
+            emit_critical += dps.critical as u32;
+
+            let emit = emit_by_enemy.entry(dps.receiver).or_insert(0);
             *emit += dps.damage + dps.absorbed;
             if take_seconds {
                 emit_by_seconds[(dps.date.timestamp() - start.unwrap()) as usize] += dps.damage;
@@ -355,27 +405,34 @@ pub fn stats_dps(list: &Vec<Dps>, start: Option<i64>, end: Option<i64>) -> (DpsS
 
     let mut opponent = vec!();
     for e in Vec::from_iter(received_by_enemy.keys().clone()){
-        opponent.push(e.to_lowercase());
+        opponent.push(names.resolve(*e).to_lowercase());
     }
     for e in Vec::from_iter(emit_by_enemy.keys().clone()){
-        opponent.push(e.to_lowercase());
+        opponent.push(names.resolve(*e).to_lowercase());
     }
 
     opponent.sort();
     opponent.dedup();
 
     (DpsStats {
-        received_by_kind,
-        emit_by_kind,
-        received_by_enemy,
-        emit_by_enemy,
+        received_by_kind: resolve(names, received_by_kind),
+        emit_by_kind: resolve(names, emit_by_kind),
+        received_by_enemy: resolve(names, received_by_enemy),
+        emit_by_enemy: resolve(names, emit_by_enemy),
         emit_by_seconds,
         emit_by_seconds_absorbed,
         received_by_seconds,
         received_by_seconds_absorbed,
+        emit_by_spell: resolve(names, emit_by_spell),
+        emit_count,
+        emit_critical,
     }, opponent)
 }
 
+fn resolve(names: &Interner, by_symbol: HashMap<Symbol, u32>) -> HashMap<String, u32> {
+    by_symbol.into_iter().map(|(symbol, value)| (names.resolve(symbol).to_string(), value)).collect()
+}
+
 #[cfg(test)]
 mod stats_tests {
     // Note this useful idiom: importing names from outer (for mod tests) scope.
@@ -383,64 +440,66 @@ mod stats_tests {
 
     #[test]
     fn assert_received_by_kind_empty() {
+        let mut names = Interner::new();
         let list = vec![
             Dps {
                 date: DateTime::from(Utc::now()),
-                emitter: "John".to_string(),
-                spell: "Spell".to_string(),
-                receiver: "SomeoneElse".to_string(),
+                emitter: names.intern("John"),
+                spell: names.intern("Spell"),
+                receiver: names.intern("SomeoneElse"),
                 damage: 100,
-                kind: "Ice".to_string(),
+                kind: names.intern("Ice"),
                 absorbed: 100,
                 critical: false,
             },
             Dps {
                 date: DateTime::from(Utc::now()),
-                emitter: "John".to_string(),
-                spell: "Spell".to_string(),
-                receiver: "You".to_string(),
+                emitter: names.intern("John"),
+                spell: names.intern("Spell"),
+                receiver: names.intern("You"),
                 damage: 100,
-                kind: "".to_string(),
+                kind: names.intern(""),
                 absorbed: 100,
                 critical: false,
             }
         ];
         assert_eq!(
-            stats_dps(&list, None, None).0.received_by_kind,
+            stats_dps(&list, &names, None, None).0.received_by_kind,
             HashMap::new()
         )
     }
 
     #[test]
     fn assert_received_by_kind_sum() {
+        let mut names = Interner::new();
         let list = vec![
             Dps {
                 date: DateTime::from(Utc::now()),
-                emitter: "John".to_string(),
-                spell: "Spell".to_string(),
-                receiver: "You".to_string(),
+                emitter: names.intern("John"),
+                spell: names.intern("Spell"),
+                receiver: names.intern("You"),
                 damage: 1000,
-                kind: "Ice".to_string(),
+                kind: names.intern("Ice"),
                 absorbed: 100,
                 critical: false,
             },
             Dps {
                 date: DateTime::from(Utc::now()),
-                emitter: "John".to_string(),
-                spell: "Spell".to_string(),
-                receiver: "You".to_string(),
+                emitter: names.intern("John"),
+                spell: names.intern("Spell"),
+                receiver: names.intern("You"),
                 damage: 100,
-                kind: "Ice".to_string(),
+                kind: names.intern("Ice"),
                 absorbed: 10,
                 critical: false,
             },
             Dps {
                 date: DateTime::from(Utc::now()),
-                emitter: "John".to_string(),
-                spell: "Spell".to_string(),
-                receiver: "You".to_string(),
+                emitter: names.intern("John"),
+                spell: names.intern("Spell"),
+                receiver: names.intern("You"),
                 damage: 123,
-                kind: "Fire".to_string(),
+                kind: names.intern("Fire"),
                 absorbed: 2000,
                 critical: false,
             }
@@ -450,71 +509,73 @@ mod stats_tests {
         res.insert("Ice".to_string(), 1210);
         res.insert("Fire".to_string(), 2123);
         assert_eq!(
-            stats_dps(&list, None, None).0.received_by_kind,
+            stats_dps(&list, &names, None, None).0.received_by_kind,
             res
         )
     }
 
     #[test]
     fn assert_emit_by_kind_empty() {
+        let mut names = Interner::new();
         let list = vec![
             Dps {
                 date: DateTime::from(Utc::now()),
-                emitter: "SomeoneElse".to_string(),
-                spell: "Spell".to_string(),
-                receiver: "John".to_string(),
+                emitter: names.intern("SomeoneElse"),
+                spell: names.intern("Spell"),
+                receiver: names.intern("John"),
                 damage: 100,
-                kind: "Ice".to_string(),
+                kind: names.intern("Ice"),
                 absorbed: 100,
                 critical: false,
             },
             Dps {
                 date: DateTime::from(Utc::now()),
-                emitter: "You".to_string(),
-                spell: "Spell".to_string(),
-                receiver: "John".to_string(),
+                emitter: names.intern("You"),
+                spell: names.intern("Spell"),
+                receiver: names.intern("John"),
                 damage: 100,
-                kind: "".to_string(),
+                kind: names.intern(""),
                 absorbed: 100,
                 critical: false,
             }
         ];
         assert_eq!(
-            stats_dps(&list, None, None).0.emit_by_kind,
+            stats_dps(&list, &names, None, None).0.emit_by_kind,
             HashMap::new()
         )
     }
 
     #[test]
     fn assert_emit_by_kind_sum() {
+        let mut names = Interner::new();
         let list = vec![
             Dps {
                 date: DateTime::from(Utc::now()),
-                emitter: "Your".to_string(),
-                spell: "Spell".to_string(),
-                receiver: "SomeoneElse".to_string(),
+                emitter: names.intern("Your"),
+                spell: names.intern("Spell"),
+                receiver: names.intern("SomeoneElse"),
                 damage: 10,
-                kind: "Ice".to_string(),
+                kind: names.intern("Ice"),
                 absorbed: 200,
                 critical: false,
             },
             Dps {
                 date: DateTime::from(Utc::now()),
-                emitter: "Your".to_string(),
-                spell: "Spell".to_string(),
-                receiver: "SomeoneElse".to_string(),
+                emitter: names.intern("Your"),
+                spell: names.intern("Spell"),
+                receiver: names.intern("SomeoneElse"),
                 damage: 600,
-                kind: "Fire".to_string(),
+                kind: names.intern("Fire"),
                 absorbed: 0,
                 critical: false,
             },
             Dps {
                 date: DateTime::from(Utc::now()),
-                emitter: "Your".to_string(),
-                spell: "Spell".to_string(),
-                receiver: "SomeoneElse".to_string(),
+                emitter: names.intern("Your"),
+                spell: names.intern("Spell"),
+                receiver: names.intern("SomeoneElse"),
                 damage: 900,
-                kind: "Fire".to_string(),
+                kind: names.intern("Fire"),
                 absorbed: 25,
                 critical: false,
             }
@@ -524,61 +585,107 @@ mod stats_tests {
         res.insert("Ice".to_string(), 210);
         res.insert("Fire".to_string(), 1525);
         assert_eq!(
-            stats_dps(&list, None, None).0.emit_by_kind,
+            stats_dps(&list, &names, None, None).0.emit_by_kind,
             res
         )
     }
 
+    #[test]
+    fn assert_emit_by_spell_and_critical() {
+        let mut names = Interner::new();
+        let list = vec![
+            Dps {
+                date: DateTime::from(Utc::now()),
+                emitter: names.intern("Your"),
+                spell: names.intern("Hammer"),
+                receiver: names.intern("SomeoneElse"),
+                damage: 10,
+                kind: names.intern("Crush"),
+                absorbed: 200,
+                critical: true,
+            },
+            Dps {
+                date: DateTime::from(Utc::now()),
+                emitter: names.intern("Your"),
+                spell: names.intern("Static Bolt"),
+                receiver: names.intern("SomeoneElse"),
+                damage: 600,
+                kind: names.intern("Nature"),
+                absorbed: 0,
+                critical: false,
+            },
+            Dps {
+                date: DateTime::from(Utc::now()),
+                emitter: names.intern("John"),
+                spell: names.intern("Hammer"),
+                receiver: names.intern("You"),
+                damage: 900,
+                kind: names.intern("Crush"),
+                absorbed: 0,
+                critical: true,
+            }
+        ];
+
+        let mut res: HashMap<String, u32> = HashMap::new();
+        res.insert("Hammer".to_string(), 210);
+        res.insert("Static Bolt".to_string(), 600);
+        let stats = stats_dps(&list, &names, None, None).0;
+        assert_eq!(stats.emit_by_spell, res);
+        assert_eq!((stats.emit_count, stats.emit_critical), (2, 1));
+    }
+
     #[test]
     fn assert_received_by_enemy_empty() {
+        let mut names = Interner::new();
         let list = vec![
             Dps {
                 date: DateTime::from(Utc::now()),
-                emitter: "John".to_string(),
-                spell: "Spell".to_string(),
-                receiver: "SomeoneElse".to_string(),
+                emitter: names.intern("John"),
+                spell: names.intern("Spell"),
+                receiver: names.intern("SomeoneElse"),
                 damage: 100,
-                kind: "Ice".to_string(),
+                kind: names.intern("Ice"),
                 absorbed: 100,
                 critical: false,
             },
         ];
         assert_eq!(
-            stats_dps(&list, None, None).0.received_by_enemy,
+            stats_dps(&list, &names, None, None).0.received_by_enemy,
             HashMap::new()
         )
     }
 
     #[test]
     fn assert_received_by_enemy_sum() {
+        let mut names = Interner::new();
         let list = vec![
             Dps {
                 date: DateTime::from(Utc::now()),
-                emitter: "John".to_string(),
-                spell: "Spell".to_string(),
-                receiver: "You".to_string(),
+                emitter: names.intern("John"),
+                spell: names.intern("Spell"),
+                receiver: names.intern("You"),
                 damage: 123,
-                kind: "Ice".to_string(),
+                kind: names.intern("Ice"),
                 absorbed: 0,
                 critical: false,
             },
             Dps {
                 date: DateTime::from(Utc::now()),
-                emitter: "John".to_string(),
-                spell: "Spell".to_string(),
-                receiver: "You".to_string(),
+                emitter: names.intern("John"),
+                spell: names.intern("Spell"),
+                receiver: names.intern("You"),
                 damage: 1000,
-                kind: "Ice".to_string(),
+                kind: names.intern("Ice"),
                 absorbed: 5,
                 critical: false,
             },
             Dps {
                 date: DateTime::from(Utc::now()),
-                emitter: "Lennon".to_string(),
-                spell: "Spell".to_string(),
-                receiver: "You".to_string(),
+                emitter: names.intern("Lennon"),
+                spell: names.intern("Spell"),
+                receiver: names.intern("You"),
                 damage: 3500,
-                kind: "Fire".to_string(),
+                kind: names.intern("Fire"),
                 absorbed: 0,
                 critical: false,
             }
@@ -588,61 +695,63 @@ mod stats_tests {
         res.insert("John".to_string(), 1128);
         res.insert("Lennon".to_string(), 3500);
         assert_eq!(
-            stats_dps(&list, None, None).0.received_by_enemy,
+            stats_dps(&list, &names, None, None).0.received_by_enemy,
             res
         )
     }
 
     #[test]
     fn assert_emit_by_enemy_empty() {
+        let mut names = Interner::new();
         let list = vec![
             Dps {
                 date: DateTime::from(Utc::now()),
-                emitter: "SomeoneElse".to_string(),
-                spell: "Spell".to_string(),
-                receiver: "You".to_string(),
+                emitter: names.intern("SomeoneElse"),
+                spell: names.intern("Spell"),
+                receiver: names.intern("You"),
                 damage: 100,
-                kind: "Ice".to_string(),
+                kind: names.intern("Ice"),
                 absorbed: 100,
                 critical: false,
             },
         ];
         assert_eq!(
-            stats_dps(&list, None, None).0.emit_by_enemy,
+            stats_dps(&list, &names, None, None).0.emit_by_enemy,
             HashMap::new()
         )
     }
 
     #[test]
     fn assert_emit_by_enemy_sum() {
+        let mut names = Interner::new();
         let list = vec![
             Dps {
                 date: DateTime::from(Utc::now()),
-                emitter: "Your".to_string(),
-                spell: "Spell".to_string(),
-                receiver: "Paul".to_string(),
+                emitter: names.intern("Your"),
+                spell: names.intern("Spell"),
+                receiver: names.intern("Paul"),
                 damage: 800,
-                kind: "Ice".to_string(),
+                kind: names.intern("Ice"),
                 absorbed: 100,
                 critical: false,
             },
             Dps {
                 date: DateTime::from(Utc::now()),
-                emitter: "Your".to_string(),
-                spell: "Spell".to_string(),
-                receiver: "Jacques".to_string(),
+                emitter: names.intern("Your"),
+                spell: names.intern("Spell"),
+                receiver: names.intern("Jacques"),
                 damage: 352,
-                kind: "Ice".to_string(),
+                kind: names.intern("Ice"),
                 absorbed: 48,
                 critical: false,
             },
             Dps {
                 date: DateTime::from(Utc::now()),
-                emitter: "Your".to_string(),
-                spell: "Spell".to_string(),
-                receiver: "Paul".to_string(),
+                emitter: names.intern("Your"),
+                spell: names.intern("Spell"),
+                receiver: names.intern("Paul"),
                 damage: 88,
-                kind: "Fire".to_string(),
+                kind: names.intern("Fire"),
                 absorbed: 1000,
                 critical: false,
             }
@@ -652,41 +761,42 @@ mod stats_tests {
         res.insert("Jacques".to_string(), 400);
         res.insert("Paul".to_string(), 1988);
         assert_eq!(
-            stats_dps(&list, None, None).0.emit_by_enemy,
+            stats_dps(&list, &names, None, None).0.emit_by_enemy,
             res
         )
     }
 
     #[test]
     fn assert_emit_by_enemy_clenup_by_time() {
+        let mut names = Interner::new();
         let list = vec![
             Dps {
                 date: DateTime::parse_from_rfc3339("2021-03-17T20:20:45.111Z").unwrap(),
-                emitter: "Your".to_string(),
-                spell: "Spell".to_string(),
-                receiver: "Paul".to_string(),
+                emitter: names.intern("Your"),
+                spell: names.intern("Spell"),
+                receiver: names.intern("Paul"),
                 damage: 800,
-                kind: "Ice".to_string(),
+                kind: names.intern("Ice"),
                 absorbed: 100,
                 critical: false,
             },
             Dps {
                 date: DateTime::parse_from_rfc3339("2021-03-17T20:50:45.111Z").unwrap(),
-                emitter: "Your".to_string(),
-                spell: "Spell".to_string(),
-                receiver: "Jacques".to_string(),
+                emitter: names.intern("Your"),
+                spell: names.intern("Spell"),
+                receiver: names.intern("Jacques"),
                 damage: 352,
-                kind: "Ice".to_string(),
+                kind: names.intern("Ice"),
                 absorbed: 48,
                 critical: false,
             },
             Dps {
                 date: DateTime::parse_from_rfc3339("2021-03-17T20:40:45.111Z").unwrap(),
-                emitter: "Your".to_string(),
-                spell: "Spell".to_string(),
-                receiver: "Paul".to_string(),
+                emitter: names.intern("Your"),
+                spell: names.intern("Spell"),
+                receiver: names.intern("Paul"),
                 damage: 88,
-                kind: "Fire".to_string(),
+                kind: names.intern("Fire"),
                 absorbed: 1000,
                 critical: false,
             }
@@ -695,7 +805,7 @@ mod stats_tests {
         let mut res: HashMap<String, u32> = HashMap::new();
         res.insert("Paul".to_string(), 1088);
 
-        let stats = stats_dps(&list, Some(DateTime::parse_from_rfc3339("2021-03-17T20:40:00.111Z").unwrap().timestamp()), Some(DateTime::parse_from_rfc3339("2021-03-17T20:42:00.111Z").unwrap().timestamp())).0;
+        let stats = stats_dps(&list, &names, Some(DateTime::parse_from_rfc3339("2021-03-17T20:40:00.111Z").unwrap().timestamp()), Some(DateTime::parse_from_rfc3339("2021-03-17T20:42:00.111Z").unwrap().timestamp())).0;
         assert_eq!(
             stats.emit_by_enemy,
             res
@@ -0,0 +1,131 @@
+#[cfg(test)]
+use regex::Regex;
+
+use chrono::prelude::*;
+use serde::{Serialize, Deserialize};
+use schemars::JsonSchema;
+
+#[cfg(test)]
+lazy_static! {
+    pub static ref RE_FOOD: Regex = Regex::new("^Your meal restored You for ([0-9]+) food.$").unwrap();
+}
+
+#[derive(Debug, PartialEq)]
+pub struct Food {
+    pub date: DateTime<FixedOffset>,
+    pub food: u32,
+}
+
+#[cfg(test)]
+pub fn parse_food(row: &str, dt: DateTime<FixedOffset>) -> Option<Food> {
+    let cap = RE_FOOD.captures(row)?;
+
+    Some(Food {
+        date: dt,
+        food: cap[1].parse::<u32>().unwrap(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    // Note this useful idiom: importing names from outer (for mod tests) scope.
+    use super::*;
+
+    #[test]
+    fn assert_food_simple() {
+        let tt = "Your meal restored You for 300 food.";
+        let dt = DateTime::from(Utc::now());
+        assert!(RE_FOOD.is_match(tt));
+        assert_eq!(
+            parse_food(tt, dt).unwrap(),
+            Food {
+                date: dt,
+                food: 300,
+            }
+        )
+    }
+
+    #[test]
+    fn assert_food_not_essence() {
+        let tt = "Your Essence Burn restored You for 250 essence.";
+        let dt = DateTime::from(Utc::now());
+        assert!(!RE_FOOD.is_match(tt));
+        assert_eq!(parse_food(tt, dt), None)
+    }
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize, JsonSchema, Clone)]
+pub struct Meal {
+    pub date: i64,
+    pub food: u32,
+}
+
+#[derive(Debug, PartialEq, Clone, Default)]
+#[derive(Serialize, Deserialize, JsonSchema)]
+pub struct FoodStats {
+    pub meals: Vec<Meal>,
+    pub total: u32,
+    pub intervals: Vec<i64>,
+}
+
+pub fn stats_food(list: &[Food]) -> FoodStats {
+    let mut meals: Vec<Meal> = list.iter().map(|food| Meal {
+        date: food.date.timestamp(),
+        food: food.food,
+    }).collect();
+
+    meals.sort_by_key(|meal| meal.date);
+
+    let total = meals.iter().map(|meal| meal.food).sum();
+
+    let intervals = meals.windows(2).map(|pair| pair[1].date - pair[0].date).collect();
+
+    FoodStats {
+        meals,
+        total,
+        intervals,
+    }
+}
+
+#[cfg(test)]
+mod stats_tests {
+    // Note this useful idiom: importing names from outer (for mod tests) scope.
+    use super::*;
+
+    #[test]
+    fn assert_food_stats_empty() {
+        assert_eq!(
+            stats_food(&[]),
+            FoodStats {
+                meals: vec![],
+                total: 0,
+                intervals: vec![],
+            }
+        )
+    }
+
+    #[test]
+    fn assert_food_stats_intervals() {
+        let list = vec![
+            Food {
+                date: DateTime::parse_from_rfc3339("2021-03-17T21:20:45.111Z").unwrap(),
+                food: 200,
+            },
+            Food {
+                date: DateTime::parse_from_rfc3339("2021-03-17T20:20:45.111Z").unwrap(),
+                food: 300,
+            },
+            Food {
+                date: DateTime::parse_from_rfc3339("2021-03-17T20:50:45.111Z").unwrap(),
+                food: 150,
+            },
+        ];
+
+        let stats = stats_food(&list);
+
+        assert_eq!(stats.total, 650);
+        assert_eq!(stats.meals.len(), 3);
+        assert_eq!(stats.meals[0].food, 300);
+        assert_eq!(stats.intervals, vec![1800, 1800]);
+    }
+}
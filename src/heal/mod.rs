@@ -1,29 +1,34 @@
+#[cfg(test)]
 use regex::Regex;
 
 use chrono::prelude::*;
 use std::collections::HashMap;
 use serde::{Serialize, Deserialize};
+use schemars::JsonSchema;
 use std::iter::FromIterator;
+use crate::intern::{Interner, Symbol};
 
 const SELF_EMITTER: &str = "Your";
 const SELF_RECEIVER: &str = "You";
 
+#[cfg(test)]
 lazy_static! {
     pub static ref RE_HEAL: Regex = Regex::new("^([^ ]+) (.+) healed (.+) for ([0-9]+)( \\(([0-9]+) absorbed\\))?( hit points)?( \\(Critical\\))?.$").unwrap();
 }
 
-#[derive(Debug, PartialEq)]
+#[derive(Debug, PartialEq, Clone)]
 pub struct Heal {
     pub date: DateTime<FixedOffset>,
-    pub emitter: String,
-    pub spell: String,
-    pub receiver: String,
+    pub emitter: Symbol,
+    pub spell: Symbol,
+    pub receiver: Symbol,
     pub heal: u32,
     pub absorbed: u32,
     pub critical: bool,
 }
 
-pub fn parse_heal(row: &str, dt: DateTime<FixedOffset>) -> Option<Heal> {
+#[cfg(test)]
+pub fn parse_heal(row: &str, dt: DateTime<FixedOffset>, names: &mut Interner) -> Option<Heal> {
     for cap in RE_HEAL.captures_iter(row) {
         let absorbed = match cap.get(6) {
             Some(_) => {
@@ -45,9 +50,9 @@ pub fn parse_heal(row: &str, dt: DateTime<FixedOffset>) -> Option<Heal> {
 
         return Some(Heal {
             date: dt,
-            emitter: cap[1].to_string(),
-            spell: cap[2].to_string(),
-            receiver: cap[3].to_string(),
+            emitter: names.intern(&cap[1]),
+            spell: names.intern(&cap[2]),
+            receiver: names.intern(&cap[3]),
             heal: cap[4].parse::<u32>().unwrap(),
             absorbed,
             critical,
@@ -66,14 +71,15 @@ mod tests {
     fn assert_self_heal_simple() {
         let tt = "Your Electrogenesis healed You for 486 hit points.";
         let dt = DateTime::from(Utc::now());
+        let mut names = Interner::new();
         assert!(RE_HEAL.is_match(tt));
         assert_eq!(
-            parse_heal(tt, dt).unwrap(),
+            parse_heal(tt, dt, &mut names).unwrap(),
             Heal {
                 date: dt,
-                emitter: "Your".to_string(),
-                spell: "Electrogenesis".to_string(),
-                receiver: "You".to_string(),
+                emitter: names.intern("Your"),
+                spell: names.intern("Electrogenesis"),
+                receiver: names.intern("You"),
                 heal: 486,
                 absorbed: 0,
                 critical: false,
@@ -85,14 +91,15 @@ mod tests {
     fn assert_self_heal_critical() {
         let tt = "Your Retaliate healed You for 162 hit points (Critical).";
         let dt = DateTime::from(Utc::now());
+        let mut names = Interner::new();
         assert!(RE_HEAL.is_match(tt));
         assert_eq!(
-            parse_heal(tt, dt).unwrap(),
+            parse_heal(tt, dt, &mut names).unwrap(),
             Heal {
                 date: dt,
-                emitter: "Your".to_string(),
-                spell: "Retaliate".to_string(),
-                receiver: "You".to_string(),
+                emitter: names.intern("Your"),
+                spell: names.intern("Retaliate"),
+                receiver: names.intern("You"),
                 heal: 162,
                 absorbed: 0,
                 critical: true,
@@ -104,14 +111,15 @@ mod tests {
     fn assert_self_heal_absorbed() {
         let tt = "Your Coalesce Life healed patibulaire for 0 (401 absorbed).";
         let dt = DateTime::from(Utc::now());
+        let mut names = Interner::new();
         assert!(RE_HEAL.is_match(tt));
         assert_eq!(
-            parse_heal(tt, dt).unwrap(),
+            parse_heal(tt, dt, &mut names).unwrap(),
             Heal {
                 date: dt,
-                emitter: "Your".to_string(),
-                spell: "Coalesce Life".to_string(),
-                receiver: "patibulaire".to_string(),
+                emitter: names.intern("Your"),
+                spell: names.intern("Coalesce Life"),
+                receiver: names.intern("patibulaire"),
                 heal: 0,
                 absorbed: 401,
                 critical: false,
@@ -123,14 +131,15 @@ mod tests {
     fn assert_heal_received() {
         let tt = "royo Divine Light healed You for 518 hit points (Critical).";
         let dt = DateTime::from(Utc::now());
+        let mut names = Interner::new();
         assert!(RE_HEAL.is_match(tt));
         assert_eq!(
-            parse_heal(tt, dt).unwrap(),
+            parse_heal(tt, dt, &mut names).unwrap(),
             Heal {
                 date: dt,
-                emitter: "royo".to_string(),
-                spell: "Divine Light".to_string(),
-                receiver: "You".to_string(),
+                emitter: names.intern("royo"),
+                spell: names.intern("Divine Light"),
+                receiver: names.intern("You"),
                 heal: 518,
                 absorbed: 0,
                 critical: true,
@@ -139,8 +148,8 @@ mod tests {
     }
 }
 
-#[derive(Debug, PartialEq)]
-#[derive(Serialize, Deserialize)]
+#[derive(Debug, PartialEq, Clone, Default)]
+#[derive(Serialize, Deserialize, JsonSchema)]
 pub struct HealStats {
     pub received_by_ally: HashMap<String, u32>,
     pub emit_by_ally: HashMap<String, u32>,
@@ -148,9 +157,17 @@ pub struct HealStats {
     pub emit_by_seconds_absorbed: Vec<u32>,
     pub received_by_seconds: Vec<u32>,
     pub received_by_seconds_absorbed: Vec<u32>,
+    /// Healing done by each of your spells.
+    #[serde(default)]
+    pub emit_by_spell: HashMap<String, u32>,
+    /// Number of your heals, and of the critical ones.
+    #[serde(default)]
+    pub emit_count: u32,
+    #[serde(default)]
+    pub emit_critical: u32,
 }
 
-pub fn stats_heal(list: &Vec<Heal>, start: Option<i64>, end: Option<i64>) -> (HealStats, Vec<String>) {
+pub fn stats_heal(list: &[Heal], names: &Interner, start: Option<i64>, end: Option<i64>) -> (HealStats, Vec<String>) {
 
     let mut received_by_ally = HashMap::new();
     let mut emit_by_ally = HashMap::new();
@@ -158,8 +175,14 @@ pub fn stats_heal(list: &Vec<Heal>, start: Option<i64>, end: Option<i64>) -> (He
     let mut emit_by_seconds_absorbed = vec![];
     let mut received_by_seconds = vec![];
     let mut received_by_seconds_absorbed = vec![];
+    let mut emit_by_spell = HashMap::new();
+    let mut emit_count = 0;
+    let mut emit_critical = 0;
     let mut take_seconds = false;
 
+    let self_emitter = names.get(SELF_EMITTER);
+    let self_receiver = names.get(SELF_RECEIVER);
+
     if start != None && end != None {
         let s = (end.unwrap() - start.unwrap() +1) as usize ;
 
@@ -176,8 +199,8 @@ pub fn stats_heal(list: &Vec<Heal>, start: Option<i64>, end: Option<i64>) -> (He
             continue;
         }
 
-        if heal.receiver == SELF_RECEIVER {
-            let rec = received_by_ally.entry(heal.emitter.to_string()).or_insert(0);
+        if Some(heal.receiver) == self_receiver {
+            let rec = received_by_ally.entry(heal.emitter).or_insert(0);
             *rec += heal.heal + heal.absorbed;
             if take_seconds {
                 received_by_seconds[(heal.date.timestamp() - start.unwrap()) as usize] += heal.heal;
@@ -185,8 +208,12 @@ pub fn stats_heal(list: &Vec<Heal>, start: Option<i64>, end: Option<i64>) -> (He
             }
         }
 
-        if heal.emitter == SELF_EMITTER {
-            let emit = emit_by_ally.entry(heal.receiver.to_string()).or_insert(0);
+        if Some(heal.emitter) == self_emitter {
+            *emit_by_spell.entry(heal.spell).or_insert(0) += heal.heal + heal.absorbed;
+            emit_count += 1;
+            emit_critical += heal.critical as u32;
+
+            let emit = emit_by_ally.entry(heal.receiver).or_insert(0);
             *emit += heal.heal + heal.absorbed;
             if take_seconds {
                 emit_by_seconds[(heal.date.timestamp() - start.unwrap()) as usize] += heal.heal;
@@ -198,25 +225,32 @@ pub fn stats_heal(list: &Vec<Heal>, start: Option<i64>, end: Option<i64>) -> (He
 
     let mut opponent = vec!();
     for e in Vec::from_iter(received_by_ally.keys().clone()){
-        opponent.push(e.to_lowercase());
+        opponent.push(names.resolve(*e).to_lowercase());
     }
     for e in Vec::from_iter(emit_by_ally.keys().clone()){
-        opponent.push(e.to_lowercase());
+        opponent.push(names.resolve(*e).to_lowercase());
     }
 
     opponent.sort();
     opponent.dedup();
 
     (HealStats {
-        received_by_ally,
-        emit_by_ally,
+        received_by_ally: resolve(names, received_by_ally),
+        emit_by_ally: resolve(names, emit_by_ally),
         emit_by_seconds,
         emit_by_seconds_absorbed,
         received_by_seconds,
         received_by_seconds_absorbed,
+        emit_by_spell: resolve(names, emit_by_spell),
+        emit_count,
+        emit_critical,
     }, opponent)
 }
 
+fn resolve(names: &Interner, by_symbol: HashMap<Symbol, u32>) -> HashMap<String, u32> {
+    by_symbol.into_iter().map(|(symbol, value)| (names.resolve(symbol).to_string(), value)).collect()
+}
+
 #[cfg(test)]
 mod stats_tests {
     // Note this useful idiom: importing names from outer (for mod tests) scope.
@@ -226,30 +260,31 @@ mod stats_tests {
 
     #[test]
     fn assert_ally_received_sum() {
+        let mut names = Interner::new();
         let list = vec![
             Heal {
                 date: DateTime::from(Utc::now()),
-                emitter: "John".to_string(),
-                spell: "Spell".to_string(),
-                receiver: "You".to_string(),
+                emitter: names.intern("John"),
+                spell: names.intern("Spell"),
+                receiver: names.intern("You"),
                 absorbed: 0,
                 critical: false,
                 heal: 150
             },
             Heal {
                 date: DateTime::from(Utc::now()),
-                emitter: "John".to_string(),
-                spell: "Spell".to_string(),
-                receiver: "You".to_string(),
+                emitter: names.intern("John"),
+                spell: names.intern("Spell"),
+                receiver: names.intern("You"),
                 absorbed: 5,
                 critical: false,
                 heal: 800
             },
             Heal {
                 date: DateTime::from(Utc::now()),
-                emitter: "Lennon".to_string(),
-                spell: "Spell".to_string(),
-                receiver: "You".to_string(),
+                emitter: names.intern("Lennon"),
+                spell: names.intern("Spell"),
+                receiver: names.intern("You"),
                 absorbed: 0,
                 critical: true,
                 heal: 1000
@@ -260,7 +295,7 @@ mod stats_tests {
         res.insert("Lennon".to_string(), 1000);
         res.insert("John".to_string(), 955);
         assert_eq!(
-            stats_heal(&list, None, None).0.received_by_ally,
+            stats_heal(&list, &names, None, None).0.received_by_ally,
             res
         )
     }
@@ -0,0 +1,274 @@
+use std::collections::HashMap;
+use std::path::Path;
+use rusqlite::{params, Connection};
+use crate::options::ParseOptions;
+use crate::query::EventType;
+use crate::session::Session;
+
+const TABLES: &str = "
+CREATE TABLE IF NOT EXISTS events (
+    character TEXT NOT NULL,
+    timestamp INTEGER NOT NULL,
+    type TEXT NOT NULL,
+    emitter TEXT NOT NULL,
+    spell TEXT NOT NULL,
+    receiver TEXT NOT NULL,
+    amount INTEGER NOT NULL,
+    absorbed INTEGER NOT NULL,
+    kind TEXT NOT NULL,
+    critical INTEGER NOT NULL,
+    occurrence INTEGER NOT NULL,
+    UNIQUE (character, timestamp, type, emitter, spell, receiver, amount, absorbed, kind, critical, occurrence)
+);
+CREATE TABLE IF NOT EXISTS fights (
+    character TEXT NOT NULL,
+    start INTEGER NOT NULL,
+    end INTEGER NOT NULL,
+    label TEXT,
+    opponents TEXT NOT NULL,
+    damage_done INTEGER NOT NULL,
+    damage_taken INTEGER NOT NULL,
+    healing_done INTEGER NOT NULL,
+    healing_taken INTEGER NOT NULL,
+    PRIMARY KEY (character, start)
+);
+CREATE INDEX IF NOT EXISTS events_by_date ON events (character, timestamp);
+";
+
+/// What an import added, a log imported again adds nothing.
+#[derive(Debug, PartialEq, Clone, Default)]
+pub struct Import {
+    pub events: usize,
+    pub fights: usize,
+}
+
+/// A fight as kept in the history, dates in seconds.
+#[derive(Debug, PartialEq, Clone)]
+pub struct FightSummary {
+    pub start: i64,
+    pub end: i64,
+    pub label: Option<String>,
+    pub opponents: Vec<String>,
+    pub damage_done: u64,
+    pub damage_taken: u64,
+    pub healing_done: u64,
+    pub healing_taken: u64,
+}
+
+fn error(error: rusqlite::Error) -> String {
+    error.to_string()
+}
+
+/// Parsed logs kept in a SQLite file, by character and date, so old logs need no parse anymore.
+pub struct History {
+    connection: Connection,
+}
+
+impl History {
+    pub fn open(path: &Path) -> Result<History, String> {
+        History::with(Connection::open(path).map_err(error)?)
+    }
+
+    pub fn open_in_memory() -> Result<History, String> {
+        History::with(Connection::open_in_memory().map_err(error)?)
+    }
+
+    fn with(connection: Connection) -> Result<History, String> {
+        connection.execute_batch(TABLES).map_err(error)?;
+        Ok(History { connection })
+    }
+
+    /// The database itself, for the questions the methods here do not answer.
+    pub fn connection(&self) -> &Connection {
+        &self.connection
+    }
+
+    /// Parse a log of `character` and keep its events and fights.
+    ///
+    /// Events already known are skipped, a fight replaces the ones it overlaps when its damage and healing
+    /// are bigger than each of theirs, so importing a log twice, or a part of a log already imported,
+    /// keeps a single and whole copy of everything. Lines using the name of `character` count as theirs
+    /// unless the options give another `self_name`.
+    pub fn import(&mut self, character: &str, contents: &str, options: &ParseOptions) -> Result<Import, String> {
+        let options = ParseOptions {
+            self_name: options.self_name.clone().or_else(|| Some(character.to_string())),
+            ..options.clone()
+        };
+
+        // a parse keeps the lines, the fights are cut apart
+        let mut session = Session::parse(contents, &options);
+        session.resplit();
+        let events = session.events(None)?;
+
+        let transaction = self.connection.transaction().map_err(error)?;
+        let mut import = Import::default();
+        {
+            let mut insert = transaction.prepare(
+                "INSERT OR IGNORE INTO events VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11)"
+            ).map_err(error)?;
+
+            // the same hit twice in the same millisecond is two hits, numbered to tell them apart
+            let mut seen = HashMap::new();
+            for event in events.iter() {
+                let event_type = match event.event_type {
+                    EventType::Damage => "damage",
+                    EventType::Heal => "heal",
+                };
+                let key = (event.timestamp, event_type, &event.emitter, &event.spell, &event.receiver, event.amount, event.absorbed, &event.kind, event.critical);
+                let occurrence = seen.entry(key).or_insert(0);
+                *occurrence += 1;
+
+                import.events += insert.execute(params![
+                    character, event.timestamp, event_type, event.emitter, event.spell, event.receiver,
+                    event.amount, event.absorbed, event.kind, event.critical, *occurrence,
+                ]).map_err(error)?;
+            }
+
+            let mut overlapping = transaction.prepare(
+                "SELECT damage_done + damage_taken + healing_done + healing_taken
+                 FROM fights WHERE character = ?1 AND start <= ?3 AND end >= ?2"
+            ).map_err(error)?;
+            let mut remove = transaction.prepare(
+                "DELETE FROM fights WHERE character = ?1 AND start <= ?3 AND end >= ?2"
+            ).map_err(error)?;
+            let mut insert = transaction.prepare(
+                "INSERT INTO fights VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)"
+            ).map_err(error)?;
+
+            for fight in session.fights() {
+                // a part of a fight already known, as cut by a log imported again from its middle, changes nothing
+                let size = (fight.damage_done() + fight.damage_taken() + fight.healing_done() + fight.healing_taken()) as i64;
+                let known: Vec<i64> = overlapping
+                    .query_map(params![character, fight.time.start, fight.time.end], |row| row.get(0))
+                    .map_err(error)?
+                    .collect::<Result<_, _>>()
+                    .map_err(error)?;
+                if known.iter().any(|known| *known >= size) {
+                    continue;
+                }
+
+                let removed = remove.execute(params![character, fight.time.start, fight.time.end]).map_err(error)?;
+                import.fights += 1usize.saturating_sub(removed);
+
+                insert.execute(params![
+                    character, fight.time.start, fight.time.end, fight.label, fight.opponent.join("\n"),
+                    fight.damage_done() as i64, fight.damage_taken() as i64, fight.healing_done() as i64, fight.healing_taken() as i64,
+                ]).map_err(error)?;
+            }
+        }
+        transaction.commit().map_err(error)?;
+
+        Ok(import)
+    }
+
+    /// The fights of `character` started from `start` to `end` seconds included, oldest first.
+    pub fn fights(&self, character: &str, start: i64, end: i64) -> Result<Vec<FightSummary>, String> {
+        let mut select = self.connection.prepare(
+            "SELECT start, end, label, opponents, damage_done, damage_taken, healing_done, healing_taken
+             FROM fights WHERE character = ?1 AND start >= ?2 AND start <= ?3 ORDER BY start"
+        ).map_err(error)?;
+
+        let fights = select.query_map(params![character, start, end], |row| {
+            let opponents: String = row.get(3)?;
+            Ok(FightSummary {
+                start: row.get(0)?,
+                end: row.get(1)?,
+                label: row.get(2)?,
+                opponents: opponents.split('\n').filter(|name| !name.is_empty()).map(String::from).collect(),
+                damage_done: row.get::<_, i64>(4)? as u64,
+                damage_taken: row.get::<_, i64>(5)? as u64,
+                healing_done: row.get::<_, i64>(6)? as u64,
+                healing_taken: row.get::<_, i64>(7)? as u64,
+            })
+        }).map_err(error)?;
+
+        fights.collect::<Result<_, _>>().map_err(error)
+    }
+
+    /// The average damage done by fight of `character` since `start` seconds, none without any fight.
+    pub fn average_damage_per_fight(&self, character: &str, start: i64) -> Result<Option<f64>, String> {
+        self.connection.query_row(
+            "SELECT AVG(damage_done) FROM fights WHERE character = ?1 AND start >= ?2",
+            params![character, start],
+            |row| row.get(0),
+        ).map_err(error)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    // Note this useful idiom: importing names from outer (for mod tests) scope.
+    use super::*;
+
+    const LOG: &str = "2021-03-31T04:35:00.289Z INFO    COMBAT    - Combat _||_ Event=[Your Hammer hit Cleric for 300 Crush damage.]
+2021-03-31T04:35:00.289Z INFO    COMBAT    - Combat _||_ Event=[Your Hammer hit Cleric for 300 Crush damage.]
+2021-03-31T04:35:02.289Z INFO    COMBAT    - Combat _||_ Event=[Your Hammer hit Cleric for 100 Crush damage.]
+2021-03-31T04:37:00.289Z INFO    COMBAT    - Combat _||_ Event=[Your Hammer hit Ranger for 200 Crush damage.]
+2021-03-31T04:37:03.289Z INFO    COMBAT    - Combat _||_ Event=[Your Hammer hit Ranger for 200 Crush damage.]
+";
+
+    fn options() -> ParseOptions {
+        ParseOptions { minimum_time: 0, ..Default::default() }
+    }
+
+    #[test]
+    fn assert_history_import_twice() {
+        let mut history = History::open_in_memory().unwrap();
+
+        let import = history.import("Aedius", LOG, &options()).unwrap();
+        assert_eq!(import, Import { events: 5, fights: 2 });
+
+        let import = history.import("Aedius", LOG, &options()).unwrap();
+        assert_eq!(import, Import { events: 0, fights: 0 });
+
+        let fights = history.fights("Aedius", 0, i64::MAX).unwrap();
+        assert_eq!(fights.len(), 2);
+        assert_eq!(fights[0].damage_done, 700);
+        assert_eq!(fights[0].opponents, vec!["cleric".to_string()]);
+        assert!(history.fights("Someone", 0, i64::MAX).unwrap().is_empty());
+    }
+
+    #[test]
+    fn assert_history_import_part() {
+        let mut history = History::open_in_memory().unwrap();
+        history.import("Aedius", LOG, &options()).unwrap();
+
+        // the log without its first line, its first fight is shorter of a hit
+        let part: Vec<&str> = LOG.lines().skip(1).collect();
+        let import = history.import("Aedius", &part.join("\n"), &options()).unwrap();
+        assert_eq!(import, Import { events: 0, fights: 0 });
+
+        let fights = history.fights("Aedius", 0, i64::MAX).unwrap();
+        assert_eq!(fights.len(), 2);
+        assert_eq!(fights[0].damage_done, 700);
+
+        // the whole log after a part of it keeps the whole fights
+        let mut history = History::open_in_memory().unwrap();
+        history.import("Aedius", &part.join("\n"), &options()).unwrap();
+        assert_eq!(history.import("Aedius", LOG, &options()).unwrap(), Import { events: 1, fights: 0 });
+        assert_eq!(history.fights("Aedius", 0, i64::MAX).unwrap()[0].damage_done, 700);
+    }
+
+    #[test]
+    fn assert_history_self_name() {
+        let mut history = History::open_in_memory().unwrap();
+        let log = LOG.replace("Your Hammer", "Aedius Hammer");
+
+        history.import("Aedius", &log, &options()).unwrap();
+        let fights = history.fights("Aedius", 0, i64::MAX).unwrap();
+        assert_eq!(fights.len(), 2);
+        assert_eq!(fights[0].damage_done, 700);
+    }
+
+    #[test]
+    fn assert_history_average() {
+        let mut history = History::open_in_memory().unwrap();
+        assert_eq!(history.average_damage_per_fight("Aedius", 0).unwrap(), None);
+
+        history.import("Aedius", LOG, &options()).unwrap();
+        assert_eq!(history.average_damage_per_fight("Aedius", 0).unwrap(), Some(550.0));
+
+        let last = history.fights("Aedius", 0, i64::MAX).unwrap()[1].start;
+        assert_eq!(history.average_damage_per_fight("Aedius", last).unwrap(), Some(400.0));
+    }
+}
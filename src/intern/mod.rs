@@ -0,0 +1,89 @@
+use std::collections::HashMap;
+use std::rc::Rc;
+
+/// A compact id standing for a name stored once in an `Interner`.
+#[derive(Debug, PartialEq, Eq, Hash, PartialOrd, Ord, Clone, Copy, Default)]
+pub struct Symbol(u32);
+
+/// Symbol table shared by every event of a parse, the empty name is always `Symbol::default()`.
+///
+/// Each name is allocated once, the map and the list share it.
+#[derive(Debug, Clone)]
+pub struct Interner {
+    ids: HashMap<Rc<str>, Symbol>,
+    names: Vec<Rc<str>>,
+}
+
+impl Default for Interner {
+    fn default() -> Self {
+        Interner::new()
+    }
+}
+
+impl Interner {
+    pub fn new() -> Interner {
+        let mut interner = Interner {
+            ids: HashMap::new(),
+            names: vec![],
+        };
+        interner.intern("");
+        interner
+    }
+
+    pub fn intern(&mut self, name: &str) -> Symbol {
+        if let Some(symbol) = self.ids.get(name) {
+            return *symbol;
+        }
+
+        let symbol = Symbol(self.names.len() as u32);
+        let name: Rc<str> = Rc::from(name);
+        self.ids.insert(name.clone(), symbol);
+        self.names.push(name);
+        symbol
+    }
+
+    pub fn get(&self, name: &str) -> Option<Symbol> {
+        self.ids.get(name).copied()
+    }
+
+    pub fn resolve(&self, symbol: Symbol) -> &str {
+        &self.names[symbol.0 as usize]
+    }
+
+    /// Number of names interned, the empty name always there is not counted.
+    pub fn len(&self) -> usize {
+        self.names.len() - 1
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    // Note this useful idiom: importing names from outer (for mod tests) scope.
+    use super::*;
+
+    #[test]
+    fn assert_intern_once() {
+        let mut names = Interner::new();
+        let a = names.intern("Cleric Defender");
+        let b = names.intern("Ranger Defender");
+
+        assert_ne!(a, b);
+        assert_eq!(names.intern("Cleric Defender"), a);
+        assert_eq!(names.resolve(b), "Ranger Defender");
+        assert_eq!(names.len(), 2);
+    }
+
+    #[test]
+    fn assert_empty_is_default() {
+        let mut names = Interner::new();
+        assert!(names.is_empty());
+        assert_eq!(names.intern(""), Symbol::default());
+        assert_eq!(names.len(), 0);
+        assert_eq!(names.resolve(Symbol::default()), "");
+        assert_eq!(names.get("unknown"), None);
+    }
+}
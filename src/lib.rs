@@ -1,155 +1,496 @@
-mod dps;
-mod heal;
-mod split;
+pub mod compare;
+pub mod csv;
+pub mod custom;
+pub mod dps;
+pub mod food;
+pub mod heal;
+#[cfg(feature = "storage")]
+pub mod history;
+pub mod intern;
+pub mod merge;
+pub mod options;
+pub mod progression;
+pub mod query;
+pub mod report;
+pub mod resource;
+pub mod schema;
+pub mod session;
+pub mod split;
+#[cfg(not(target_arch = "wasm32"))]
+pub mod tail;
+pub mod tokenizer;
+pub mod unknown;
 
 use wasm_bindgen::prelude::*;
 
+#[cfg(test)]
 #[macro_use]
 extern crate lazy_static;
 
-use regex::Regex;
+use custom::*;
 use dps::*;
+use food::*;
 use heal::*;
+use unknown::*;
+use intern::Interner;
+use resource::*;
+use session::{Progress, Session};
+use query::{EventPage, Filter};
+use options::ParseOptions;
 use chrono::prelude::{DateTime, FixedOffset};
+use tokenizer::*;
 use serde::{Serialize, Deserialize};
-use crate::split::{split_in_fight, FightTimer};
+use schemars::JsonSchema;
+use std::collections::HashMap;
+use crate::split::{Activity, ActivityKind, FightTimer};
 
-struct Data {
+pub(crate) struct Data {
+    pub names: Interner,
     pub dps: Vec<Dps>,
     pub heal: Vec<Heal>,
+    pub food: Vec<Food>,
+    pub resource: Vec<Resource>,
+    pub unknown: Vec<UnknownEvent>,
+    pub custom: Vec<CustomEvent>,
+    pub patterns: Vec<CompiledPattern>,
+    pub self_name: Option<String>,
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+/// Version of the shape of `ExportedData`, bumped when a field is renamed, removed or changes type.
+pub const SCHEMA_VERSION: u32 = 1;
+
+#[derive(Serialize, Deserialize, JsonSchema, Debug)]
 pub struct ExportedData {
+    pub schema_version: u32,
     pub dps_stats: DpsStats,
     pub heal_stats: HealStats,
+    pub food_stats: FoodStats,
+    pub resource_stats: ResourceStats,
+    pub custom_stats: CustomStats,
     pub errors : Vec<String>,
+    pub warnings : Vec<String>,
+    pub unknown: Vec<UnknownEvent>,
+    pub unknown_shapes: HashMap<String, u32>,
     pub fights: Vec<Fight>,
 }
 
-#[derive(Serialize, Deserialize, Debug)]
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone)]
 pub struct Fight{
+    #[serde(default)]
+    pub label: Option<String>,
     pub opponent: Vec<String>,
     pub time : FightTimer,
     pub dps_stats: DpsStats,
     pub heal_stats: HealStats,
+    pub resource_stats: ResourceStats,
+    pub custom_stats: CustomStats,
+    /// The first page of the hits and heals of the fight, only when a timeline is asked.
+    #[serde(default)]
+    pub events: Option<EventPage>,
+}
+
+/// Sum of a table by name.
+fn total(by_name: &HashMap<String, u32>) -> u64 {
+    by_name.values().map(|amount| *amount as u64).sum()
+}
+
+/// Totals of a fight, damage and healing count the absorbed part too.
+impl Fight {
+    pub fn duration(&self) -> i64 {
+        self.time.end - self.time.start
+    }
+
+    pub fn damage_done(&self) -> u64 {
+        total(&self.dps_stats.emit_by_enemy)
+    }
+
+    pub fn damage_taken(&self) -> u64 {
+        total(&self.dps_stats.received_by_enemy)
+    }
+
+    pub fn healing_done(&self) -> u64 {
+        total(&self.heal_stats.emit_by_ally)
+    }
+
+    pub fn healing_taken(&self) -> u64 {
+        total(&self.heal_stats.received_by_ally)
+    }
 }
 
+#[wasm_bindgen(typescript_custom_section)]
+const TYPESCRIPT: &'static str = include_str!("../schema/exported_data.d.ts");
+
 #[wasm_bindgen]
-pub fn parse(contents: &str, time_between: i64, minimum_time: i64) -> JsValue {
-    let to_export = parse_rust(contents, time_between, minimum_time);
+extern "C" {
+    #[wasm_bindgen(typescript_type = "ExportedData")]
+    pub type JsExportedData;
+    #[wasm_bindgen(typescript_type = "Fight")]
+    pub type JsFight;
+    #[wasm_bindgen(typescript_type = "Fight | null")]
+    pub type JsOptionFight;
+    #[wasm_bindgen(typescript_type = "QueryResult")]
+    pub type JsQueryResult;
+    #[wasm_bindgen(typescript_type = "EventPage")]
+    pub type JsEventPage;
+    #[wasm_bindgen(typescript_type = "ParseOptions | undefined")]
+    pub type JsParseOptions;
+    #[wasm_bindgen(typescript_type = "Filter | undefined")]
+    pub type JsFilter;
+    #[wasm_bindgen(typescript_type = "(progress: Progress) => boolean | void")]
+    pub type JsProgressCallback;
+    #[wasm_bindgen(typescript_type = "FightComparison")]
+    pub type JsFightComparison;
+    #[wasm_bindgen(typescript_type = "CharacterSession[]")]
+    pub type JsCharacterSessions;
+    #[wasm_bindgen(typescript_type = "Progression[]")]
+    pub type JsProgressions;
+}
 
-    JsValue::from_serde(&to_export).unwrap()
+#[wasm_bindgen]
+pub fn parse(contents: &str, options: JsParseOptions) -> Result<JsExportedData, JsValue> {
+    let options = options_from_js(options)?;
+
+    Ok(to_js(&parse_rust(contents, &options)))
 }
 
-fn parse_rust(contents: &str, time_between: i64, minimum_time: i64) -> ExportedData {
-    let re_event = Regex::new("([-0-9T:\\.]+Z).*Event=\\[(.*)\\]").unwrap();
+/// As `parse` for the logs of several launches, see `merge::merge_logs`.
+#[wasm_bindgen(js_name = parseFiles)]
+pub fn parse_files(contents: Vec<String>, options: JsParseOptions) -> Result<JsExportedData, JsValue> {
+    let options = options_from_js(options)?;
+    let contents: Vec<&str> = contents.iter().map(|contents| contents.as_str()).collect();
 
-    let mut data = Data {
-        dps: Default::default(),
-        heal: Default::default(),
-    };
+    Ok(to_js(&parse_files_rust(&contents, &options)))
+}
+
+/// A single HTML file showing an export offline, dates in the `timezone` and charts by the `bucket` of the options.
+#[wasm_bindgen(js_name = htmlReport)]
+pub fn html_report(data: JsExportedData, options: JsParseOptions) -> Result<String, JsValue> {
+    let options = options_from_js(options)?;
+    let data: ExportedData = try_from_js(data.into())?;
 
-    let mut date_list = vec![];
+    Ok(report::html_report(&data, &options)?)
+}
 
-    let mut nb = 0;
+/// How the fight `second` did against `first`, both taken from any export.
+#[wasm_bindgen(js_name = compareFights)]
+pub fn compare_fights(first: JsFight, second: JsFight) -> Result<JsFightComparison, JsValue> {
+    let first: Fight = try_from_js(first.into())?;
+    let second: Fight = try_from_js(second.into())?;
 
-    let lines = contents.lines();
-    let mut errors = vec![];
+    Ok(to_js(&compare::compare(&first, &second)))
+}
 
-    for line in lines {
+/// The trends of each character over several parsed logs, see `progression::progression`.
+#[wasm_bindgen]
+pub fn progression(sessions: JsCharacterSessions) -> Result<JsProgressions, JsValue> {
+    let sessions: Vec<progression::CharacterSession> = try_from_js(sessions.into())?;
 
-        if !re_event.is_match(line){
-            println!("NO MATCH : {:?}",line);
-        }
+    Ok(to_js(&progression::progression(&sessions)))
+}
 
-        for cap in re_event.captures_iter(line) {
-            let d = DateTime::parse_from_rfc3339(&cap[1]).unwrap();
+/// As `parse`, calling `progress` every `options.progress_every` lines, the parse is cancelled when it returns `false`.
+#[wasm_bindgen(js_name = parseWithProgress)]
+pub fn parse_with_progress(contents: &str, options: JsParseOptions, progress: JsProgressCallback) -> Result<JsExportedData, JsValue> {
+    let options = options_from_js(options)?;
+    let mut session = parse_session(contents, &options, &progress)?;
+    session.resplit();
 
-            if data.parse_row(&cap[2], d) {
-                date_list.push(d);
-            } else {
-                errors.push(cap[2].to_string());
-            }
-            nb = nb + 1;
-        }
+    Ok(to_js(&session.export()))
+}
+
+fn parse_session(contents: &str, options: &ParseOptions, progress: &JsProgressCallback) -> Result<Session, JsValue> {
+    let progress: &js_sys::Function = progress.unchecked_ref();
+    let report = |current: &Progress| match progress.call1(&JsValue::NULL, &to_js(current)) {
+        Ok(result) => result.as_bool() != Some(false),
+        Err(_) => false,
+    };
+
+    Session::parse_with_progress(contents, options, report).ok_or_else(|| JsValue::from("cancelled"))
+}
+
+/// A value for the page, typed in TypeScript by `J`, maps are plain objects as in JSON.
+fn to_js<T: Serialize, J: JsCast>(value: &T) -> J {
+    value.serialize(&serde_wasm_bindgen::Serializer::json_compatible()).unwrap().unchecked_into()
+}
+
+/// A value given back by the page, the error says what does not fit.
+fn try_from_js<T: serde::de::DeserializeOwned>(value: JsValue) -> Result<T, JsValue> {
+    serde_wasm_bindgen::from_value(value).map_err(JsValue::from)
+}
+
+/// An argument from the page, `default` when it is left undefined or null.
+fn try_from_js_or<T: serde::de::DeserializeOwned>(value: JsValue, default: T) -> Result<T, JsValue> {
+    if value.is_undefined() || value.is_null() {
+        Ok(default)
+    } else {
+        try_from_js(value)
     }
+}
+
+/// The options given by the page, the defaults when left out, an error when one cannot be used.
+fn options_from_js(options: JsParseOptions) -> Result<ParseOptions, JsValue> {
+    let options: ParseOptions = try_from_js_or(options.into(), ParseOptions::default())?;
+    options.check()?;
+    Ok(options)
+}
+
+/// The parsed log kept in the wasm memory, changing how fights are split does not read the text again.
+#[wasm_bindgen(js_name = Session)]
+pub struct JsSession {
+    session: Session,
+}
 
-    let ( dps_stats, _) = stats_dps(&data.dps, None, None);
-    let ( heal_stats, _) = stats_heal(&data.heal, None, None);
+#[wasm_bindgen(js_class = Session)]
+impl JsSession {
+    #[wasm_bindgen(constructor)]
+    pub fn new(contents: &str, options: JsParseOptions) -> Result<JsSession, JsValue> {
+        let options = options_from_js(options)?;
 
-    let fight_timers = split_in_fight(date_list, time_between, minimum_time);
-    let mut fight = vec![];
+        Ok(JsSession {
+            session: Session::parse(contents, &options),
+        })
+    }
 
-    for timer in fight_timers {
+    /// As the constructor for the logs of several launches, see `parseFiles`.
+    #[wasm_bindgen(js_name = fromFiles)]
+    pub fn from_files(contents: Vec<String>, options: JsParseOptions) -> Result<JsSession, JsValue> {
+        let options = options_from_js(options)?;
+        let contents: Vec<&str> = contents.iter().map(|contents| contents.as_str()).collect();
 
-        let (dps_stats, mut opponent ) = stats_dps(&data.dps, Some(timer.start), Some(timer.end));
-        let (heal_stats, mut opponent_heal ) = stats_heal(&data.heal, Some(timer.start), Some(timer.end));
-        opponent.append(&mut opponent_heal);
+        Ok(JsSession {
+            session: Session::parse(&merge::merge_logs(&contents), &options),
+        })
+    }
 
-        opponent.sort();
-        opponent.dedup();
+    /// As the constructor, see `parseWithProgress` for `progress`.
+    #[wasm_bindgen(js_name = withProgress)]
+    pub fn with_progress(contents: &str, options: JsParseOptions, progress: JsProgressCallback) -> Result<JsSession, JsValue> {
+        let options = options_from_js(options)?;
 
-        fight.push(Fight {
-            time: timer.clone(),
-            dps_stats,
-            heal_stats,
-            opponent
+        Ok(JsSession {
+            session: parse_session(contents, &options, &progress)?,
         })
     }
 
-    let to_export = ExportedData {
-        dps_stats,
-        heal_stats,
-        errors,
-        fights: fight
-    };
-    to_export
-}
+    /// Split the fights again and return the whole export, the times left out keep the ones of the options.
+    pub fn resplit(&mut self, time_between: Option<u32>, minimum_time: Option<u32>) -> JsExportedData {
+        let mut options = self.session.options().clone();
+        options.time_between = time_between.map_or(options.time_between, i64::from);
+        options.minimum_time = minimum_time.map_or(options.minimum_time, i64::from);
+        self.session.set_options(options);
+        self.session.resplit();
+
+        to_js(&self.session.export())
+    }
+
+    /// Change the options used by the next `resplit`, the patterns, strictness and self name need a new session.
+    #[wasm_bindgen(js_name = setOptions)]
+    pub fn set_options(&mut self, options: JsParseOptions) -> Result<(), JsValue> {
+        self.session.set_options(options_from_js(options)?);
+        Ok(())
+    }
+
+    #[wasm_bindgen(js_name = fightCount)]
+    pub fn fight_count(&self) -> usize {
+        self.session.fights().len()
+    }
+
+    pub fn fight(&self, index: usize) -> JsOptionFight {
+        to_js(&self.session.fights().get(index))
+    }
+
+    /// The stats from `start` to `end` seconds included, as a fight, the range is cut to the dates of the log.
+    pub fn stats(&self, start: i64, end: i64) -> Result<JsFight, JsValue> {
+        Ok(to_js(&self.session.stats(start, end)?))
+    }
+
+    /// How the fight `second` did against the fight `first`.
+    pub fn compare(&self, first: usize, second: usize) -> Result<JsFightComparison, JsValue> {
+        Ok(to_js(&self.session.compare(first, second)?))
+    }
+
+    /// The events kept by a filter and their stats, see `query::Filter` for its fields.
+    pub fn query(&self, filter: JsFilter) -> Result<JsQueryResult, JsValue> {
+        let filter: Filter = try_from_js_or(filter.into(), Filter::default())?;
+        Ok(to_js(&self.session.query(&filter)))
+    }
+
+    /// Add the first `page_size` events of each fight to the next splits, `0` to stop.
+    #[wasm_bindgen(js_name = setTimeline)]
+    pub fn set_timeline(&mut self, page_size: usize) {
+        self.session.set_timeline(if page_size == 0 { None } else { Some(page_size) });
+    }
 
+    /// The events of the fight `index` from `offset`, at most `limit` of them.
+    pub fn timeline(&self, index: usize, offset: usize, limit: usize) -> Result<JsEventPage, JsValue> {
+        let page = self.session.timeline(index, offset, limit)?;
+        Ok(to_js(&page))
+    }
+
+    /// The hits and heals of the fight `index`, or of the whole log, as csv.
+    #[wasm_bindgen(js_name = eventsCsv)]
+    pub fn events_csv(&self, index: Option<usize>) -> Result<String, JsValue> {
+        Ok(csv::events_csv(&self.session.events(index)?, &self.session.options().offset()?)?)
+    }
+
+    #[wasm_bindgen(js_name = fightsCsv)]
+    pub fn fights_csv(&self) -> Result<String, JsValue> {
+        Ok(csv::fights_csv(self.session.fights(), &self.session.options().offset()?)?)
+    }
+
+    /// The total by spell of the fight `index`, or of the whole log, as csv.
+    #[wasm_bindgen(js_name = spellsCsv)]
+    pub fn spells_csv(&self, index: Option<usize>) -> Result<String, JsValue> {
+        Ok(csv::spells_csv(&self.session.events(index)?))
+    }
+
+    pub fn merge(&mut self, index: usize) -> Result<(), JsValue> {
+        self.session.merge(index).map_err(JsValue::from)
+    }
+
+    #[wasm_bindgen(js_name = splitFight)]
+    pub fn split_fight(&mut self, index: usize, at: i64) -> Result<(), JsValue> {
+        self.session.split_fight(index, at).map_err(JsValue::from)
+    }
 
+    pub fn trim(&mut self, index: usize, start: i64, end: i64) -> Result<(), JsValue> {
+        self.session.trim(index, start, end).map_err(JsValue::from)
+    }
 
-lazy_static! {
-    static ref RE_FOOD: Regex = Regex::new("^Your meal restored You for ([0-9]+) food.$").unwrap();
+    pub fn rename(&mut self, index: usize, label: &str) -> Result<(), JsValue> {
+        self.session.rename(index, label).map_err(JsValue::from)
+    }
 
-    static ref RE_SELF_RESOURCE: Regex = Regex::new("^Your (.+) (restored|drained) You for ([0-9]+) (.+).$").unwrap();
+    pub fn export(&self) -> JsExportedData {
+        to_js(&self.session.export())
+    }
 
-    static ref RE_RESOURCE: Regex = Regex::new("^(.+) (restored|drained) You for ([0-9]+) (.+).$").unwrap();
+    /// The whole export as a single HTML file to share, see `htmlReport`.
+    #[wasm_bindgen(js_name = htmlReport)]
+    pub fn html_report(&self) -> Result<String, JsValue> {
+        Ok(report::html_report(&self.session.export(), self.session.options())?)
+    }
 }
 
+pub fn parse_rust(contents: &str, options: &ParseOptions) -> ExportedData {
+    let mut session = Session::parse(contents, options);
+    session.resplit();
 
+    session.export()
+}
+
+/// One export for the logs of several launches, ordered and without the lines they have in common.
+pub fn parse_files_rust(contents: &[&str], options: &ParseOptions) -> ExportedData {
+    parse_rust(&merge::merge_logs(contents), options)
+}
+
+/// The events of a sorted list dated from `start` to `end` included, found by binary search.
+fn between<T>(list: &[T], start: i64, end: i64, timestamp: impl Fn(&T) -> i64) -> &[T] {
+    let from = list.partition_point(|event| timestamp(event) < start);
+    let to = list.partition_point(|event| timestamp(event) <= end);
+
+    &list[from..to.max(from)]
+}
+
+/// Sort a list whose first `sorted` items already are, the others usually come after them in order and need nothing.
+fn sort_after<T, K: Ord>(list: &mut [T], sorted: usize, key: impl Fn(&T) -> K) {
+    if list[sorted.saturating_sub(1)..].windows(2).any(|pair| key(&pair[0]) > key(&pair[1])) {
+        list.sort_by_key(key);
+    }
+}
 
 impl Data {
-    fn parse_row(&mut self, row: &str, dt: DateTime<FixedOffset>) -> bool {
-        if RE_FOOD.is_match(row) {
-            //TODO
-            return true;
-        }
+    /// Sort every event list by date once, so each fight is a contiguous slice of it.
+    fn sort(&mut self) {
+        self.sort_after(Default::default());
+    }
 
-        if RE_RESOURCE.is_match(row) {
-            //TODO
-            return true;
-        }
+    /// Length of each event list, what is sorted before adding lines.
+    fn lengths(&self) -> [usize; 4] {
+        [self.dps.len(), self.heal.len(), self.custom.len(), self.resource.len()]
+    }
 
-        if RE_SELF_RESOURCE.is_match(row) {
-            //TODO
-            return true;
-        }
+    /// As `sort` when the lists were sorted up to `lengths`.
+    fn sort_after(&mut self, lengths: [usize; 4]) {
+        sort_after(&mut self.dps, lengths[0], |dps| dps.date);
+        sort_after(&mut self.heal, lengths[1], |heal| heal.date);
+        sort_after(&mut self.custom, lengths[2], |custom| custom.date);
+        sort_after(&mut self.resource, lengths[3], |resource| resource.date);
+    }
+
+    /// Store the event of the row, `None` when the row is not understood.
+    fn parse_row(&mut self, row: &str, dt: DateTime<FixedOffset>) -> Option<Activity> {
+        let mut activity = Activity {
+            date: dt,
+            kind: ActivityKind::Custom,
+            opponent: None,
+        };
+
+        if let Some(mut event) = parse_event(row) {
+            if self.self_name.as_deref() == Some(event.emitter) {
+                event.emitter = "Your";
+            }
+            if self.self_name.as_deref() == Some(event.receiver) {
+                event.receiver = "You";
+            }
 
-        if RE_DPS.is_match(row) {
-            let dps = parse_dps(row, dt).unwrap();
-            self.dps.push(dps);
-            return true;
+            match event.verb {
+                Verb::Restored if event.is_food() => {
+                    activity.kind = ActivityKind::Food;
+                    self.food.push(Food {
+                        date: dt,
+                        food: event.amount,
+                    });
+                }
+                Verb::Restored | Verb::Drained => {
+                    activity.kind = ActivityKind::Resource;
+                    self.resource.push(Resource {
+                        date: dt,
+                        emitter: self.names.intern(event.emitter),
+                        spell: self.names.intern(event.spell),
+                        amount: event.amount,
+                        kind: self.names.intern(event.kind),
+                        drained: event.verb == Verb::Drained,
+                    });
+                }
+                Verb::Hit => {
+                    activity.kind = if event.is_to_self() { ActivityKind::DamageReceived } else { ActivityKind::DamageDealt };
+                    let dps = Dps {
+                        date: dt,
+                        emitter: self.names.intern(event.emitter),
+                        spell: self.names.intern(event.spell),
+                        receiver: self.names.intern(event.receiver),
+                        damage: event.amount,
+                        kind: self.names.intern(event.kind),
+                        absorbed: event.absorbed,
+                        critical: event.critical,
+                    };
+                    activity.opponent = dps.opponent(&self.names);
+                    self.dps.push(dps);
+                }
+                Verb::Healed => {
+                    activity.kind = if event.is_from_self() { ActivityKind::HealDone } else { ActivityKind::HealReceived };
+                    self.heal.push(Heal {
+                        date: dt,
+                        emitter: self.names.intern(event.emitter),
+                        spell: self.names.intern(event.spell),
+                        receiver: self.names.intern(event.receiver),
+                        heal: event.amount,
+                        absorbed: event.absorbed,
+                        critical: event.critical,
+                    });
+                }
+            }
+            return Some(activity);
         }
 
-        if RE_HEAL.is_match(row) {
-            let heal = parse_heal(row, dt).unwrap();
-            self.heal.push(heal);
-            return true;
+        if let Some(custom) = parse_custom(&self.patterns, row, dt) {
+            self.custom.push(custom);
+            return Some(activity);
         }
 
-        println!("{:?}", row);
-        return false;
+        self.unknown.push(parse_unknown(row, dt));
+        None
     }
 }
 
@@ -157,10 +498,21 @@ impl Data {
 mod tests {
     // Note this useful idiom: importing names from outer (for mod tests) scope.
     use super::*;
+    use crate::schema::{assert_snapshot, type_of};
+    use schemars::schema::{Schema, SchemaObject, SingleOrVec};
+    use crate::split::{FightSplitter, GapSplitter, SplitStrategy};
     use std::fs::{File};
     use std::io::BufReader;
     use std::io::prelude::*;
 
+    /// Parse with a splitter of our own instead of the one of the options.
+    fn parse_with(contents: &str, splitter: &dyn FightSplitter, options: &ParseOptions) -> ExportedData {
+        let mut session = Session::parse(contents, options);
+        session.split(splitter);
+
+        session.export()
+    }
+
     #[test]
     fn assert_parse() {
         let file = File::open("./fixtures/file1.txt").unwrap();
@@ -168,10 +520,10 @@ mod tests {
         let mut contents = String::new();
         buf_reader.read_to_string(&mut contents).unwrap();
 
-        let calc = parse_rust(contents.as_str(), 30, 0);
+        let calc = parse_rust(contents.as_str(), &ParseOptions { minimum_time: 0, ..Default::default() });
 
         assert_eq!(calc.errors.len(), 0);
-        assert_eq!(calc.fights.len(), 13);
+        assert_eq!(calc.fights.len(), 15);
 
         println!("{:?}", calc)
     }
@@ -183,12 +535,200 @@ mod tests {
         let mut contents = String::new();
         buf_reader.read_to_string(&mut contents).unwrap();
 
-        let calc = parse_rust(contents.as_str(), 30, 30);
+        let calc = parse_rust(contents.as_str(), &ParseOptions::default());
 
         assert_eq!(calc.errors.len(), 0);
-        assert_eq!(calc.fights.len(), 7);
+        assert_eq!(calc.fights.len(), 6);
 
         println!("{:?}", calc)
     }
 
+    #[test]
+    fn assert_parse_every_activity() {
+        let file = File::open("./fixtures/file1.txt").unwrap();
+        let mut buf_reader = BufReader::new(file);
+        let mut contents = String::new();
+        buf_reader.read_to_string(&mut contents).unwrap();
+
+        // the gap splitter alone, without the filter on the kinds of activity of the options
+        let calc = parse_with(contents.as_str(), &GapSplitter { time_between: 30, minimum_time: 0 }, &ParseOptions::default());
+        assert_eq!(calc.errors.len(), 0);
+        assert_eq!(calc.fights.len(), 13);
+
+        let calc = parse_with(contents.as_str(), &GapSplitter { time_between: 30, minimum_time: 30 }, &ParseOptions::default());
+        assert_eq!(calc.fights.len(), 7);
+    }
+
+    #[test]
+    fn assert_between_sorted() {
+        let list = vec![1, 3, 3, 5, 8, 13];
+
+        assert_eq!(between(&list, 3, 8, |n| *n), &[3, 3, 5, 8]);
+        assert_eq!(between(&list, 9, 12, |n| *n), &[] as &[i64]);
+        assert_eq!(between(&list, 0, 100, |n| *n), &list[..]);
+    }
+
+    #[test]
+    fn assert_sort_after() {
+        let mut list = vec![1, 3, 5, 6, 7];
+        sort_after(&mut list, 3, |n| *n);
+        assert_eq!(list, vec![1, 3, 5, 6, 7]);
+
+        let mut list = vec![1, 3, 5, 2, 7];
+        sort_after(&mut list, 3, |n| *n);
+        assert_eq!(list, vec![1, 2, 3, 5, 7]);
+
+        let mut list = vec![4, 3];
+        sort_after(&mut list, 0, |n| *n);
+        assert_eq!(list, vec![3, 4]);
+    }
+
+    #[test]
+    fn assert_parse_opponent_strategy() {
+        let file = File::open("./fixtures/file1.txt").unwrap();
+        let mut buf_reader = BufReader::new(file);
+        let mut contents = String::new();
+        buf_reader.read_to_string(&mut contents).unwrap();
+
+        let calc = parse_with(contents.as_str(), SplitStrategy::Opponent { window: 10 }.splitter(30, 0).as_ref(), &ParseOptions::default());
+        assert_eq!(calc.errors.len(), 0);
+        assert_eq!(calc.fights.len(), 13);
+
+        let calc = parse_with(contents.as_str(), SplitStrategy::Opponent { window: 0 }.splitter(30, 0).as_ref(), &ParseOptions::default());
+        assert!(calc.fights.len() > 13);
+        for pair in calc.fights.windows(2) {
+            assert!(pair[0].time.end <= pair[1].time.start);
+        }
+    }
+
+    #[test]
+    fn assert_parse_offensive_only() {
+        let file = File::open("./fixtures/file1.txt").unwrap();
+        let mut buf_reader = BufReader::new(file);
+        let mut contents = String::new();
+        buf_reader.read_to_string(&mut contents).unwrap();
+
+        let calc = parse_rust(contents.as_str(), &ParseOptions { minimum_time: 0, ..Default::default() });
+
+        assert_eq!(calc.errors.len(), 0);
+        assert_eq!(calc.fights.len(), 15);
+        assert!(calc.resource_stats.restored_by_kind["essence"] > 0);
+        assert!(calc.fights.iter().any(|fight| fight.resource_stats.restored_by_kind.contains_key("essence")));
+        assert!(calc.fights.iter().any(|fight| fight.heal_stats.emit_by_seconds.iter().sum::<u32>() > 0));
+    }
+
+    #[test]
+    fn assert_parse_unknown_as_warning() {
+        let contents = "2021-03-31T04:36:01.225Z INFO    COMBAT    - Combat _||_ Event=[Your Static Bolt hit Cleric Defender for 100 Nature damage.]
+2021-03-31T04:36:02.225Z INFO    COMBAT    - Combat _||_ Event=[Your Shield Bash stunned Cleric Defender for 3 seconds.]
+2021-03-31T04:36:03.225Z INFO    COMBAT    - Combat _||_ Event=[Your Shield Bash stunned Ranger Defender for 2 seconds.]";
+
+        let strict = parse_rust(contents, &ParseOptions { strict: true, ..Default::default() });
+        assert_eq!(strict.errors.len(), 2);
+        assert_eq!(strict.warnings.len(), 0);
+
+        let calc = parse_rust(contents, &ParseOptions::default());
+        assert_eq!(calc.errors.len(), 0);
+        assert_eq!(calc.warnings.len(), 2);
+        assert_eq!(calc.unknown.len(), 2);
+        assert_eq!(calc.unknown[0].tokens[3], "stunned");
+        assert_eq!(calc.unknown_shapes.len(), 2);
+        assert_eq!(calc.unknown_shapes["Your Shield Bash stunned Cleric Defender for # seconds."], 1);
+    }
+
+    #[test]
+    fn assert_parse_custom_pattern() {
+        let contents = "2021-03-31T04:36:01.225Z INFO    COMBAT    - Combat _||_ Event=[Your Static Bolt hit Cleric Defender for 100 Nature damage.]
+2021-03-31T04:36:02.225Z INFO    COMBAT    - Combat _||_ Event=[Your Shield Bash stunned Cleric Defender for 3 seconds.]
+2021-03-31T04:36:03.225Z INFO    COMBAT    - Combat _||_ Event=[Your Shield Bash stunned Ranger Defender for 2 seconds.]";
+
+        let patterns = vec![CustomPattern {
+            name: "stun".to_string(),
+            regex: "^(?P<emitter>[^ ]+) (?P<spell>.+) stunned (?P<receiver>.+) for (?P<amount>[0-9]+) seconds.$".to_string(),
+        }];
+
+        let fight_kinds = vec![ActivityKind::DamageDealt, ActivityKind::Custom];
+        let calc = parse_rust(contents, &ParseOptions { strict: true, minimum_time: 0, fight_kinds, patterns, ..Default::default() });
+        assert_eq!(calc.errors.len(), 0);
+        assert_eq!(calc.unknown.len(), 0);
+        assert_eq!(calc.custom_stats.count_by_name["stun"], 2);
+        assert_eq!(calc.custom_stats.amount_by_name["stun"], 5);
+        assert_eq!(calc.fights.len(), 1);
+        assert_eq!(calc.fights[0].custom_stats.count_by_name["stun"], 2);
+    }
+
+    /// The keys and value types of a serialised value, arrays are reduced to their first item
+    /// and maps to their type, so names found in the log do not show.
+    fn shape(value: &serde_json::Value, schema: &Schema, definitions: &schemars::Map<String, Schema>) -> serde_json::Value {
+        use serde_json::Value;
+
+        let object = |schema: &Schema| match schema {
+            Schema::Object(schema) => schema.clone(),
+            Schema::Bool(_) => SchemaObject::default(),
+        };
+        let mut schema = object(schema);
+        if let Some(reference) = &schema.reference {
+            schema = object(&definitions[reference.trim_start_matches("#/definitions/")]);
+        }
+        // an option is the type or null, the value tells which
+        if let Some(Some(alternatives)) = schema.subschemas.as_ref().map(|subschemas| subschemas.any_of.as_ref()) {
+            if !value.is_null() {
+                let alternative = alternatives.iter().find(|alternative| type_of(alternative) != "null");
+                return shape(value, alternative.unwrap_or(&Schema::Bool(true)), definitions);
+            }
+        }
+
+        let object = schema.object.as_ref();
+        match value {
+            Value::Null => Value::from("null"),
+            Value::Bool(_) => Value::from("boolean"),
+            Value::Number(_) => Value::from("number"),
+            Value::String(_) => Value::from("string"),
+            Value::Array(items) => {
+                let item = match schema.array.as_ref().and_then(|array| array.items.as_ref()) {
+                    Some(SingleOrVec::Single(item)) => item.as_ref().clone(),
+                    _ => Schema::Bool(true),
+                };
+                Value::Array(items.iter().take(1).map(|value| shape(value, &item, definitions)).collect())
+            }
+            Value::Object(_) if object.is_none_or(|object| object.properties.is_empty()) => Value::from(type_of(&Schema::Object(schema.clone()))),
+            Value::Object(fields) => Value::Object(fields.iter()
+                .map(|(key, field)| {
+                    let property = object.and_then(|object| object.properties.get(key)).cloned().unwrap_or(Schema::Bool(true));
+                    (key.clone(), shape(field, &property, definitions))
+                })
+                .collect()),
+        }
+    }
+
+    #[test]
+    fn assert_parse_files() {
+        // the fixture holds the same log twice, keep one
+        let contents = std::fs::read_to_string("./fixtures/file1.txt").unwrap();
+        let lines: Vec<&str> = contents.lines().take(3527).collect();
+        let contents = lines.join("\n");
+        let (first, second) = (lines[..2000].join("\n"), lines[1500..].join("\n"));
+        let options = ParseOptions { minimum_time: 0, ..Default::default() };
+
+        let whole = parse_rust(&contents, &options);
+        let calc = parse_files_rust(&[&second, &first], &options);
+        assert_eq!(calc.fights.len(), whole.fights.len());
+        assert_eq!(calc.dps_stats.emit_by_enemy, whole.dps_stats.emit_by_enemy);
+        assert_eq!(calc.heal_stats.received_by_ally, whole.heal_stats.received_by_ally);
+    }
+
+    #[test]
+    fn assert_fixture_shape() {
+        let file = File::open("./fixtures/file1.txt").unwrap();
+        let mut buf_reader = BufReader::new(file);
+        let mut contents = String::new();
+        buf_reader.read_to_string(&mut contents).unwrap();
+
+        let calc = parse_rust(contents.as_str(), &ParseOptions { minimum_time: 0, ..Default::default() });
+        assert_eq!(calc.schema_version, SCHEMA_VERSION);
+
+        let root = schema::schema();
+        let shape = shape(&serde_json::to_value(&calc).unwrap(), &Schema::Object(root.schema), &root.definitions);
+        assert_snapshot("./fixtures/file1.shape.json", &(serde_json::to_string_pretty(&shape).unwrap() + "\n"));
+    }
 }
\ No newline at end of file
@@ -0,0 +1,93 @@
+use std::collections::HashMap;
+use crate::tokenizer::split_line;
+
+/// The lines of a log with the date they are sorted by, a line without date keeps the one before it.
+fn dated_lines(contents: &str) -> Vec<(&str, &str)> {
+    let mut date = "";
+
+    contents.lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| {
+            if let Some((line_date, _)) = split_line(line) {
+                date = line_date;
+            }
+            (date, line)
+        })
+        .collect()
+}
+
+/// One log out of several ones, as written by the game on each launch.
+///
+/// The logs are ordered by their first date and their lines sorted by date, a line found in more than one log,
+/// where two logs overlap, is only kept as many times as in the log having it the most.
+pub fn merge_logs(contents: &[&str]) -> String {
+    let mut logs: Vec<Vec<(&str, &str)>> = contents.iter().map(|contents| dated_lines(contents)).collect();
+    logs.sort_by_key(|lines| lines.first().map(|(date, _)| *date));
+
+    let mut most: HashMap<&str, usize> = HashMap::new();
+    for lines in logs.iter() {
+        let mut count: HashMap<&str, usize> = HashMap::new();
+        for (_, line) in lines.iter() {
+            *count.entry(line).or_default() += 1;
+        }
+        for (line, count) in count {
+            let most = most.entry(line).or_default();
+            *most = (*most).max(count);
+        }
+    }
+
+    // the sort is stable, lines of the same date stay in the order of their logs
+    let mut lines: Vec<(&str, &str)> = logs.into_iter().flatten().collect();
+    lines.sort_by_key(|(date, _)| *date);
+
+    let mut merged = String::new();
+    for (_, line) in lines {
+        let left = most.get_mut(line).unwrap();
+        if *left > 0 {
+            *left -= 1;
+            merged += line;
+            merged += "\n";
+        }
+    }
+
+    merged
+}
+
+#[cfg(test)]
+mod tests {
+    // Note this useful idiom: importing names from outer (for mod tests) scope.
+    use super::*;
+
+    const FIRST: &str = "2021-03-31T04:35:00.289Z INFO    COMBAT    - Combat _||_ Event=[Your Fall hit You for 100 Crush damage.]
+2021-03-31T04:35:01.289Z INFO    COMBAT    - Combat _||_ Event=[Your Fall hit You for 100 Crush damage.]
+2021-03-31T04:35:02.289Z INFO    COMBAT    - Combat _||_ Event=[Your Hammer hit Cleric for 300 Crush damage.]
+";
+    const SECOND: &str = "2021-03-31T04:35:02.289Z INFO    COMBAT    - Combat _||_ Event=[Your Hammer hit Cleric for 300 Crush damage.]
+2021-03-31T04:35:03.289Z INFO    COMBAT    - Combat _||_ Event=[Your Hammer hit Cleric for 200 Crush damage.]
+2021-03-31T04:35:03.289Z INFO    COMBAT    - Combat _||_ Event=[Your Hammer hit Cleric for 200 Crush damage.]
+";
+
+    #[test]
+    fn assert_merge_logs() {
+        let merged = merge_logs(&[SECOND, FIRST]);
+        let lines: Vec<&str> = merged.lines().collect();
+
+        assert_eq!(lines.len(), 5);
+        assert!(lines[0].contains("04:35:00"));
+        assert!(lines[2].contains("for 300"));
+        assert!(lines[3].contains("for 200") && lines[4].contains("for 200"));
+    }
+
+    #[test]
+    fn assert_merge_overlapping_fixture() {
+        let log = std::fs::read_to_string("./fixtures/file1.txt").unwrap();
+        // the fixture holds the same log twice, keep one
+        let lines: Vec<&str> = log.lines().take(3527).collect();
+        let first = lines[..2000].join("\n");
+        let second = lines[1500..].join("\n");
+
+        let merged = merge_logs(&[&second, &first]);
+        assert_eq!(merged.lines().count(), 3527);
+        assert_eq!(merged, merge_logs(&[&lines.join("\n")]));
+    }
+}
@@ -0,0 +1,149 @@
+use chrono::prelude::FixedOffset;
+use serde::{Serialize, Deserialize};
+use schemars::JsonSchema;
+use crate::custom::CustomPattern;
+use crate::split::{ActivityKind, KindSplitter, SplitStrategy, DEFAULT_FIGHT_KINDS};
+
+/// Everything a parse can be told, each field left out keeps its default.
+#[derive(Debug, PartialEq, Clone)]
+#[derive(Serialize, Deserialize, JsonSchema)]
+#[serde(default)]
+pub struct ParseOptions {
+    /// Seconds without activity that end a fight.
+    pub time_between: i64,
+    /// Fights shorter than this many seconds are dropped.
+    pub minimum_time: i64,
+    pub strategy: SplitStrategy,
+    /// The activities that start and keep a fight alive.
+    pub fight_kinds: Vec<ActivityKind>,
+    /// Your character name, lines using it count as yours like the ones with `Your` and `You`.
+    /// A single word, as the log starts a line with the first word of its emitter.
+    pub self_name: Option<String>,
+    /// Width in seconds of each item of the `*_by_seconds` arrays.
+    pub bucket: i64,
+    /// Unknown lines are errors instead of warnings.
+    pub strict: bool,
+    /// Minutes east of UTC used to write dates.
+    pub timezone: i32,
+    pub patterns: Vec<CustomPattern>,
+    /// Number of events given with each fight, none when left out.
+    pub timeline: Option<usize>,
+    pub sections: Sections,
+    /// Lines between two progress reports.
+    pub progress_every: usize,
+}
+
+impl Default for ParseOptions {
+    fn default() -> Self {
+        ParseOptions {
+            time_between: 30,
+            minimum_time: 30,
+            strategy: SplitStrategy::default(),
+            fight_kinds: DEFAULT_FIGHT_KINDS.to_vec(),
+            self_name: None,
+            bucket: 1,
+            strict: false,
+            timezone: 0,
+            patterns: vec![],
+            timeline: None,
+            sections: Sections::default(),
+            progress_every: 10000,
+        }
+    }
+}
+
+impl ParseOptions {
+    pub fn splitter(&self) -> KindSplitter {
+        KindSplitter {
+            kinds: self.fight_kinds.clone(),
+            inner: self.strategy.splitter(self.time_between, self.minimum_time),
+        }
+    }
+
+    /// An error for the first option that cannot be used.
+    pub fn check(&self) -> Result<(), String> {
+        self.strategy.check()?;
+        self.offset()?;
+        match &self.self_name {
+            Some(name) if name.is_empty() || name.contains(char::is_whitespace) => {
+                Err(format!("self name {:?} does not fit, it is a single word", name))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// The offset of `timezone`, an error when it is not less than a day.
+    pub fn offset(&self) -> Result<FixedOffset, String> {
+        self.timezone.checked_mul(60)
+            .and_then(FixedOffset::east_opt)
+            .ok_or_else(|| format!("timezone {} does not fit, it is less than a day of minutes", self.timezone))
+    }
+}
+
+/// The parts of the export to compute, the ones left out stay empty.
+#[derive(Debug, PartialEq, Clone)]
+#[derive(Serialize, Deserialize, JsonSchema)]
+#[serde(default)]
+pub struct Sections {
+    pub dps: bool,
+    pub heal: bool,
+    pub food: bool,
+    pub resource: bool,
+    pub custom: bool,
+    pub unknown: bool,
+    pub fights: bool,
+}
+
+impl Default for Sections {
+    fn default() -> Self {
+        Sections {
+            dps: true,
+            heal: true,
+            food: true,
+            resource: true,
+            custom: true,
+            unknown: true,
+            fights: true,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    // Note this useful idiom: importing names from outer (for mod tests) scope.
+    use super::*;
+
+    #[test]
+    fn assert_options_defaults() {
+        let options: ParseOptions = serde_json::from_str(r#"{ "time_between": 60, "sections": { "unknown": false } }"#).unwrap();
+
+        assert_eq!(options.time_between, 60);
+        assert_eq!(options.minimum_time, 30);
+        assert_eq!(options.bucket, 1);
+        assert_eq!(options.fight_kinds, DEFAULT_FIGHT_KINDS.to_vec());
+        assert!(!options.sections.unknown);
+        assert!(options.sections.dps);
+    }
+
+    #[test]
+    fn assert_options_offset() {
+        let options = ParseOptions { timezone: 120, ..Default::default() };
+        assert_eq!(options.offset(), Ok(FixedOffset::east(7200)));
+
+        let options = ParseOptions { timezone: 100000, ..Default::default() };
+        assert!(options.offset().is_err());
+        assert!(options.check().is_err());
+
+        let options = ParseOptions { timezone: i32::MIN, ..Default::default() };
+        assert!(options.offset().is_err());
+    }
+
+    #[test]
+    fn assert_options_self_name() {
+        let options = ParseOptions { self_name: Some("Aedius".to_string()), ..Default::default() };
+        assert!(options.check().is_ok());
+
+        let options = ParseOptions { self_name: Some("Sir Aedius".to_string()), ..Default::default() };
+        assert!(options.check().is_err());
+    }
+}
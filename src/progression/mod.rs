@@ -0,0 +1,198 @@
+use std::collections::{BTreeMap, HashMap};
+use serde::{Serialize, Deserialize};
+use schemars::JsonSchema;
+use crate::ExportedData;
+
+/// The custom pattern counted as deaths, add one named so to the options to match the death lines,
+/// the deaths of a log parsed without it are unknown.
+pub const DEATH: &str = "death";
+
+/// A parsed log and the character who wrote it.
+#[derive(Serialize, Deserialize, JsonSchema, Debug)]
+pub struct CharacterSession {
+    pub character: String,
+    pub data: ExportedData,
+}
+
+/// What a character did in one session, over its fights.
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone, PartialEq)]
+pub struct SessionPoint {
+    /// Start of the first fight and end of the last one, in seconds.
+    pub start: i64,
+    pub end: i64,
+    pub fights: usize,
+    pub damage_per_fight: f64,
+    pub healing_per_fight: f64,
+    /// Damage and healing over the time spent in fights.
+    pub damage_per_second: f64,
+    pub healing_per_second: f64,
+    /// Part of your hits and heals that were critical, from 0 to 1.
+    pub critical_rate: f64,
+    /// Unknown when the log was parsed without the `death` pattern.
+    pub deaths: Option<u32>,
+    /// Deaths over the time from `start` to `end`, deaths also happen between fights.
+    pub deaths_per_hour: Option<f64>,
+    /// Part of your damage and healing done by each spell, from 0 to 1.
+    pub spell_share: HashMap<String, f64>,
+}
+
+/// How much of the damage and healing of a character a spell did in the first and the last session.
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone, PartialEq)]
+pub struct SpellShift {
+    pub spell: String,
+    pub first: f64,
+    pub last: f64,
+    pub change: f64,
+}
+
+/// The trends of a character across sessions, changes go from the first session to the last.
+#[derive(Serialize, Deserialize, JsonSchema, Debug, Clone, PartialEq)]
+pub struct Progression {
+    pub character: String,
+    /// One point by session with fights, oldest first.
+    pub sessions: Vec<SessionPoint>,
+    pub damage_per_fight_change: f64,
+    pub healing_per_fight_change: f64,
+    pub critical_rate_change: f64,
+    /// Unknown unless both sessions know their deaths.
+    pub deaths_per_hour_change: Option<f64>,
+    /// The spells whose share moved the most first.
+    pub spells: Vec<SpellShift>,
+}
+
+fn ratio(value: f64, total: f64) -> f64 {
+    if total > 0.0 { value / total } else { 0.0 }
+}
+
+fn point(data: &ExportedData) -> Option<SessionPoint> {
+    let start = data.fights.iter().map(|fight| fight.time.start).min()?;
+    let end = data.fights.iter().map(|fight| fight.time.end).max()?;
+    let fights = data.fights.len() as f64;
+
+    let damage: u64 = data.fights.iter().map(|fight| fight.damage_done()).sum();
+    let healing: u64 = data.fights.iter().map(|fight| fight.healing_done()).sum();
+    let seconds: i64 = data.fights.iter().map(|fight| fight.duration().max(1)).sum();
+
+    let count = data.dps_stats.emit_count + data.heal_stats.emit_count;
+    let critical = data.dps_stats.emit_critical + data.heal_stats.emit_critical;
+    let deaths = data.custom_stats.count_by_name.get(DEATH).copied();
+    let hours = (end - start).max(1) as f64 / 3600.0;
+
+    let mut by_spell: HashMap<String, u64> = HashMap::new();
+    for (spell, amount) in data.dps_stats.emit_by_spell.iter().chain(data.heal_stats.emit_by_spell.iter()) {
+        *by_spell.entry(spell.clone()).or_insert(0) += *amount as u64;
+    }
+    let total: u64 = by_spell.values().sum();
+
+    Some(SessionPoint {
+        start,
+        end,
+        fights: data.fights.len(),
+        damage_per_fight: damage as f64 / fights,
+        healing_per_fight: healing as f64 / fights,
+        damage_per_second: ratio(damage as f64, seconds as f64),
+        healing_per_second: ratio(healing as f64, seconds as f64),
+        critical_rate: ratio(critical as f64, count as f64),
+        deaths,
+        deaths_per_hour: deaths.map(|deaths| deaths as f64 / hours),
+        spell_share: by_spell.into_iter().map(|(spell, amount)| (spell, ratio(amount as f64, total as f64))).collect(),
+    })
+}
+
+fn shifts(first: &SessionPoint, last: &SessionPoint) -> Vec<SpellShift> {
+    let mut spells: Vec<&String> = first.spell_share.keys().chain(last.spell_share.keys()).collect();
+    spells.sort();
+    spells.dedup();
+
+    let mut shifts: Vec<SpellShift> = spells.into_iter().map(|spell| {
+        let first = first.spell_share.get(spell).copied().unwrap_or(0.0);
+        let last = last.spell_share.get(spell).copied().unwrap_or(0.0);
+        SpellShift { spell: spell.clone(), first, last, change: last - first }
+    }).collect();
+
+    shifts.sort_by(|a, b| b.change.abs().total_cmp(&a.change.abs()).then(a.spell.cmp(&b.spell)));
+    shifts
+}
+
+/// The progression of each character found in `sessions`, by name, sessions without fights are left out.
+pub fn progression(sessions: &[CharacterSession]) -> Vec<Progression> {
+    let mut by_character: BTreeMap<&str, Vec<SessionPoint>> = BTreeMap::new();
+    for session in sessions.iter() {
+        if let Some(point) = point(&session.data) {
+            by_character.entry(&session.character).or_default().push(point);
+        }
+    }
+
+    by_character.into_iter().map(|(character, mut points)| {
+        points.sort_by_key(|point| point.start);
+        let (first, last) = (&points[0], &points[points.len() - 1]);
+
+        Progression {
+            character: character.to_string(),
+            damage_per_fight_change: last.damage_per_fight - first.damage_per_fight,
+            healing_per_fight_change: last.healing_per_fight - first.healing_per_fight,
+            critical_rate_change: last.critical_rate - first.critical_rate,
+            deaths_per_hour_change: last.deaths_per_hour.zip(first.deaths_per_hour).map(|(last, first)| last - first),
+            spells: shifts(first, last),
+            sessions: points,
+        }
+    }).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    // Note this useful idiom: importing names from outer (for mod tests) scope.
+    use super::*;
+    use crate::custom::CustomPattern;
+    use crate::options::ParseOptions;
+    use crate::parse_rust;
+
+    const FIRST: &str = "2021-03-30T20:00:00.000Z INFO    COMBAT    - Combat _||_ Event=[Your Hammer hit Cleric for 300 Crush damage.]
+2021-03-30T20:00:01.000Z INFO    COMBAT    - Combat _||_ Event=[Your Hammer hit Cleric for 100 Crush damage.]
+2021-03-30T20:00:02.000Z INFO    COMBAT    - Combat _||_ Event=[Your Static Bolt hit Cleric for 100 Nature damage (Critical).]
+2021-03-30T20:00:04.000Z INFO    COMBAT    - Combat _||_ Event=[Cleric Hammer hit You for 100 Crush damage.]";
+    const SECOND: &str = "2021-03-31T20:00:00.000Z INFO    COMBAT    - Combat _||_ Event=[Your Static Bolt hit Cleric for 500 Nature damage (Critical).]
+2021-03-31T20:00:01.000Z INFO    COMBAT    - Combat _||_ Event=[Your Static Bolt hit Cleric for 500 Nature damage (Critical).]
+2021-03-31T20:00:02.000Z INFO    COMBAT    - Combat _||_ Event=[Your Hammer hit Cleric for 200 Crush damage (Critical).]
+2021-03-31T20:00:03.000Z INFO    COMBAT    - Combat _||_ Event=[Your Death Surge hit Cleric for 100 Nature damage.]
+2021-03-31T20:00:04.000Z INFO    COMBAT    - Combat _||_ Event=[You were killed by Cleric.]";
+
+    fn session(character: &str, contents: &str) -> CharacterSession {
+        let patterns = vec![CustomPattern {
+            name: DEATH.to_string(),
+            regex: r"^You were killed by".to_string(),
+        }];
+        let options = ParseOptions { minimum_time: 0, patterns, ..Default::default() };
+
+        CharacterSession { character: character.to_string(), data: parse_rust(contents, &options) }
+    }
+
+    #[test]
+    fn assert_progression_without_deaths() {
+        let options = ParseOptions { minimum_time: 0, ..Default::default() };
+        let first = CharacterSession { character: "Aedius".to_string(), data: parse_rust(FIRST, &options) };
+        let report = progression(&[first, session("Aedius", SECOND)]);
+
+        assert_eq!(report[0].sessions[0].deaths, None);
+        assert_eq!(report[0].sessions[0].deaths_per_hour, None);
+        assert_eq!(report[0].sessions[1].deaths, Some(1));
+        assert_eq!(report[0].deaths_per_hour_change, None);
+        assert_eq!(session("Aedius", FIRST).data.custom_stats.count_by_name[DEATH], 0);
+    }
+
+    #[test]
+    fn assert_progression() {
+        let report = progression(&[session("Aedius", SECOND), session("Aedius", FIRST), session("Other", "")]);
+
+        assert_eq!(report.len(), 1);
+        let aedius = &report[0];
+        assert_eq!(aedius.sessions.len(), 2);
+        assert_eq!(aedius.sessions[0].damage_per_fight, 500.0);
+        assert_eq!(aedius.damage_per_fight_change, 800.0);
+        assert_eq!(aedius.critical_rate_change, 0.75 - 1.0 / 3.0);
+        assert_eq!(aedius.sessions[1].deaths, Some(1));
+        assert_eq!(aedius.deaths_per_hour_change, Some(3600.0 / 3.0));
+        assert_eq!(aedius.spells[0].spell, "Hammer");
+        assert_eq!(aedius.spells[0].first, 0.8);
+    }
+}
@@ -0,0 +1,289 @@
+use serde::{Serialize, Deserialize};
+use schemars::JsonSchema;
+use crate::dps::*;
+use crate::heal::*;
+use crate::intern::{Interner, Symbol};
+
+/// Which events a query keeps, every field left empty matches everything.
+#[derive(Debug, PartialEq, Clone, Default)]
+#[derive(Serialize, Deserialize, JsonSchema)]
+#[serde(default)]
+pub struct Filter {
+    pub start: Option<i64>,
+    pub end: Option<i64>,
+    pub emitter: Option<String>,
+    pub receiver: Option<String>,
+    pub spell: Option<String>,
+    pub kind: Option<String>,
+    pub critical_only: bool,
+    pub min_amount: u32,
+}
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+#[derive(Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "snake_case")]
+pub enum EventType {
+    Damage,
+    Heal,
+}
+
+/// One hit or heal with its names resolved, `timestamp` is in milliseconds.
+#[derive(Debug, PartialEq, Clone)]
+#[derive(Serialize, Deserialize, JsonSchema)]
+pub struct RawEvent {
+    pub timestamp: i64,
+    #[serde(rename = "type")]
+    pub event_type: EventType,
+    pub emitter: String,
+    pub spell: String,
+    pub receiver: String,
+    pub amount: u32,
+    pub absorbed: u32,
+    pub kind: String,
+    pub critical: bool,
+}
+
+impl RawEvent {
+    pub fn from_dps(dps: &Dps, names: &Interner) -> RawEvent {
+        RawEvent {
+            timestamp: dps.date.timestamp_millis(),
+            event_type: EventType::Damage,
+            emitter: names.resolve(dps.emitter).to_string(),
+            spell: names.resolve(dps.spell).to_string(),
+            receiver: names.resolve(dps.receiver).to_string(),
+            amount: dps.damage,
+            absorbed: dps.absorbed,
+            kind: names.resolve(dps.kind).to_string(),
+            critical: dps.critical,
+        }
+    }
+
+    pub fn from_heal(heal: &Heal, names: &Interner) -> RawEvent {
+        RawEvent {
+            timestamp: heal.date.timestamp_millis(),
+            event_type: EventType::Heal,
+            emitter: names.resolve(heal.emitter).to_string(),
+            spell: names.resolve(heal.spell).to_string(),
+            receiver: names.resolve(heal.receiver).to_string(),
+            amount: heal.heal,
+            absorbed: heal.absorbed,
+            kind: String::new(),
+            critical: heal.critical,
+        }
+    }
+}
+
+#[derive(Debug, PartialEq, Clone)]
+#[derive(Serialize, Deserialize, JsonSchema)]
+pub struct QueryResult {
+    pub events: Vec<RawEvent>,
+    pub dps_stats: DpsStats,
+    pub heal_stats: HealStats,
+}
+
+/// A slice of the events of a fight, `total` counts all of them.
+#[derive(Debug, PartialEq, Clone)]
+#[derive(Serialize, Deserialize, JsonSchema)]
+pub struct EventPage {
+    pub offset: usize,
+    pub total: usize,
+    pub events: Vec<RawEvent>,
+}
+
+/// The hits and heals from `offset`, at most `limit` of them, by date.
+pub fn timeline(dps: &[Dps], heal: &[Heal], names: &Interner, offset: usize, limit: usize) -> EventPage {
+    enum Row<'a> {
+        Dps(&'a Dps),
+        Heal(&'a Heal),
+    }
+
+    let mut rows: Vec<(i64, Row)> = dps.iter().map(|dps| (dps.date.timestamp_millis(), Row::Dps(dps)))
+        .chain(heal.iter().map(|heal| (heal.date.timestamp_millis(), Row::Heal(heal))))
+        .collect();
+    rows.sort_by_key(|(date, _)| *date);
+
+    let events = rows.iter()
+        .skip(offset)
+        .take(limit)
+        .map(|(_, row)| match row {
+            Row::Dps(dps) => RawEvent::from_dps(dps, names),
+            Row::Heal(heal) => RawEvent::from_heal(heal, names),
+        })
+        .collect();
+
+    EventPage {
+        offset,
+        total: rows.len(),
+        events,
+    }
+}
+
+/// The filter with its names looked up once, a name never seen in the log matches nothing.
+struct Matcher {
+    emitter: Option<Option<Symbol>>,
+    receiver: Option<Option<Symbol>>,
+    spell: Option<Option<Symbol>>,
+    kind: Option<Option<Symbol>>,
+    critical_only: bool,
+    min_amount: u32,
+}
+
+impl Matcher {
+    fn new(filter: &Filter, names: &Interner) -> Matcher {
+        let lookup = |name: &Option<String>| name.as_ref().map(|name| names.get(name));
+
+        Matcher {
+            emitter: lookup(&filter.emitter),
+            receiver: lookup(&filter.receiver),
+            spell: lookup(&filter.spell),
+            kind: lookup(&filter.kind),
+            critical_only: filter.critical_only,
+            min_amount: filter.min_amount,
+        }
+    }
+
+    fn matches(&self, emitter: Symbol, receiver: Symbol, spell: Symbol, kind: Option<Symbol>, amount: u32, critical: bool) -> bool {
+        let same = |wanted: Option<Option<Symbol>>, symbol: Option<Symbol>| match wanted {
+            None => true,
+            Some(wanted) => wanted.is_some() && wanted == symbol,
+        };
+
+        same(self.emitter, Some(emitter))
+            && same(self.receiver, Some(receiver))
+            && same(self.spell, Some(spell))
+            && same(self.kind, kind)
+            && (critical || !self.critical_only)
+            && amount >= self.min_amount
+    }
+}
+
+/// The hits and heals kept by `filter`, by date, and their stats without the `*_by_seconds` arrays.
+pub fn query(dps: &[Dps], heal: &[Heal], names: &Interner, filter: &Filter) -> QueryResult {
+    let matcher = Matcher::new(filter, names);
+    let in_range = |timestamp: i64| timestamp >= filter.start.unwrap_or(0) && timestamp <= filter.end.unwrap_or(i64::MAX);
+
+    let dps: Vec<Dps> = dps.iter()
+        .filter(|dps| in_range(dps.date.timestamp()))
+        .filter(|dps| matcher.matches(dps.emitter, dps.receiver, dps.spell, Some(dps.kind), dps.damage + dps.absorbed, dps.critical))
+        .cloned()
+        .collect();
+    let heal: Vec<Heal> = heal.iter()
+        .filter(|heal| in_range(heal.date.timestamp()))
+        .filter(|heal| matcher.matches(heal.emitter, heal.receiver, heal.spell, None, heal.heal + heal.absorbed, heal.critical))
+        .cloned()
+        .collect();
+
+    let mut events: Vec<RawEvent> = dps.iter().map(|dps| RawEvent::from_dps(dps, names))
+        .chain(heal.iter().map(|heal| RawEvent::from_heal(heal, names)))
+        .collect();
+    events.sort_by_key(|event| event.timestamp);
+
+    // the events are already in range, the stats need no arrays by second as wide as the filter
+    let (dps_stats, _) = stats_dps(&dps, names, None, None);
+    let (heal_stats, _) = stats_heal(&heal, names, None, None);
+
+    QueryResult {
+        events,
+        dps_stats,
+        heal_stats,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    // Note this useful idiom: importing names from outer (for mod tests) scope.
+    use super::*;
+    use chrono::prelude::*;
+
+    fn dps(names: &mut Interner, date: &str, emitter: &str, receiver: &str, damage: u32, kind: &str, critical: bool) -> Dps {
+        Dps {
+            date: DateTime::parse_from_rfc3339(date).unwrap(),
+            emitter: names.intern(emitter),
+            spell: names.intern("Hammer"),
+            receiver: names.intern(receiver),
+            damage,
+            kind: names.intern(kind),
+            absorbed: 0,
+            critical,
+        }
+    }
+
+    #[test]
+    fn assert_query_filter() {
+        let mut names = Interner::new();
+        let list = vec![
+            dps(&mut names, "2021-03-17T20:40:45.111Z", "Cleric", "You", 100, "Fire", false),
+            dps(&mut names, "2021-03-17T20:40:46.222Z", "Cleric", "You", 50, "Fire", true),
+            dps(&mut names, "2021-03-17T20:40:47.111Z", "Cleric", "You", 70, "Ice", false),
+            dps(&mut names, "2021-03-17T20:40:48.111Z", "Ranger", "You", 30, "Fire", false),
+            dps(&mut names, "2021-03-17T20:45:48.111Z", "Cleric", "You", 30, "Fire", false),
+        ];
+        let start = DateTime::parse_from_rfc3339("2021-03-17T20:40:00.000Z").unwrap().timestamp();
+
+        let filter = Filter {
+            start: Some(start),
+            end: Some(start + 60),
+            emitter: Some("Cleric".to_string()),
+            kind: Some("Fire".to_string()),
+            ..Default::default()
+        };
+        let result = query(&list, &[], &names, &filter);
+        assert_eq!(result.events.len(), 2);
+        assert_eq!(result.events[1].timestamp % 1000, 222);
+        assert_eq!(result.dps_stats.received_by_kind["Fire"], 150);
+
+        let critical = query(&list, &[], &names, &Filter { critical_only: true, ..filter.clone() });
+        assert_eq!(critical.events.len(), 1);
+
+        let strong = query(&list, &[], &names, &Filter { min_amount: 60, ..Default::default() });
+        assert_eq!(strong.events.len(), 2);
+
+        let unknown = query(&list, &[], &names, &Filter { emitter: Some("Nobody".to_string()), ..Default::default() });
+        assert_eq!(unknown.events.len(), 0);
+    }
+
+    #[test]
+    fn assert_query_any_range() {
+        let mut names = Interner::new();
+        let list = vec![
+            dps(&mut names, "2021-03-17T20:40:45.111Z", "Cleric", "You", 100, "Fire", false),
+            dps(&mut names, "2021-03-17T20:40:46.222Z", "Cleric", "You", 50, "Fire", true),
+        ];
+        let start = DateTime::parse_from_rfc3339("2021-03-17T20:40:00.000Z").unwrap().timestamp();
+
+        let reversed = query(&list, &[], &names, &Filter { start: Some(start + 60), end: Some(start), ..Default::default() });
+        assert_eq!(reversed.events.len(), 0);
+
+        let wide = query(&list, &[], &names, &Filter { start: Some(0), end: Some(i64::MAX), ..Default::default() });
+        assert_eq!(wide.events.len(), 2);
+        assert_eq!(wide.dps_stats.received_by_enemy["Cleric"], 150);
+        assert!(wide.dps_stats.received_by_seconds.is_empty());
+    }
+
+    #[test]
+    fn assert_timeline_page() {
+        let mut names = Interner::new();
+        let list = vec![
+            dps(&mut names, "2021-03-17T20:40:45.111Z", "Cleric", "You", 100, "Fire", false),
+            dps(&mut names, "2021-03-17T20:40:46.222Z", "Cleric", "You", 50, "Fire", true),
+            dps(&mut names, "2021-03-17T20:40:47.333Z", "Cleric", "You", 70, "Ice", false),
+        ];
+        let heal = vec![Heal {
+            date: DateTime::parse_from_rfc3339("2021-03-17T20:40:46.500Z").unwrap(),
+            emitter: names.intern("Your"),
+            spell: names.intern("Prayer"),
+            receiver: names.intern("You"),
+            heal: 40,
+            absorbed: 0,
+            critical: false,
+        }];
+
+        let page = timeline(&list, &heal, &names, 1, 2);
+        assert_eq!(page.total, 4);
+        assert_eq!(page.events.len(), 2);
+        assert_eq!(page.events[0].amount, 50);
+        assert_eq!(page.events[1].event_type, EventType::Heal);
+
+        assert_eq!(timeline(&list, &heal, &names, 3, 10).events.len(), 1);
+    }
+}
@@ -0,0 +1,211 @@
+use chrono::prelude::*;
+use std::collections::HashMap;
+use crate::options::ParseOptions;
+use crate::{ExportedData, Fight};
+
+const STYLE: &str = "
+body { font-family: sans-serif; margin: 2em; color: #222; }
+table { border-collapse: collapse; margin-bottom: 1em; }
+th, td { padding: 2px 10px; text-align: right; border-bottom: 1px solid #ddd; }
+th:first-child, td:first-child, td.name { text-align: left; }
+section { margin-bottom: 3em; }
+.charts { display: flex; flex-wrap: wrap; gap: 2em; }
+.bar { background: #d4526e; height: 12px; }
+.heal .bar { background: #33b2df; }
+.legend span { display: inline-block; margin-right: 1em; }
+";
+
+const DAMAGE_DONE: &str = "#d4526e";
+const DAMAGE_TAKEN: &str = "#f9a3a4";
+const HEALING_DONE: &str = "#33b2df";
+const HEALING_TAKEN: &str = "#69d2e7";
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;").replace('<', "&lt;").replace('>', "&gt;").replace('"', "&quot;")
+}
+
+fn time(timestamp: i64, offset: &FixedOffset, format: &str) -> Result<String, String> {
+    offset.timestamp_opt(timestamp, 0).single()
+        .map(|date| date.format(format).to_string())
+        .ok_or_else(|| format!("{} is not a date", timestamp))
+}
+
+/// The lines of a table by name, the biggest first, as bars relative to the biggest.
+fn bars(title: &str, class: &str, by_name: &HashMap<String, u32>) -> String {
+    let mut lines: Vec<(&String, &u32)> = by_name.iter().collect();
+    lines.sort_by(|(a_name, a), (b_name, b)| b.cmp(a).then(a_name.cmp(b_name)));
+    let max = lines.first().map_or(1, |(_, amount)| **amount).max(1);
+
+    let mut html = format!("<div class=\"{}\"><h4>{}</h4><table>", class, title);
+    for (name, amount) in lines {
+        html += &format!(
+            "<tr><td class=\"name\">{}</td><td>{}</td><td><div class=\"bar\" style=\"width: {}px\"></div></td></tr>",
+            escape(name), amount, *amount as u64 * 200 / max as u64,
+        );
+    }
+    html + "</table></div>"
+}
+
+/// Each series by `bucket` seconds as a line, all on the scale of the biggest value.
+fn timeline(series: &[(&str, Vec<u32>)], bucket: i64) -> String {
+    let (width, height) = (800.0, 160.0);
+    let length = series.iter().map(|(_, values)| values.len()).max().unwrap_or(0).max(2);
+    let max = series.iter().flat_map(|(_, values)| values.iter()).copied().max().unwrap_or(0).max(1);
+
+    let mut svg = format!("<svg width=\"{}\" height=\"{}\" viewBox=\"0 0 {} {}\">", width, height + 20.0, width, height + 20.0);
+    let per = if bucket > 1 { format!("{}s", bucket) } else { "s".to_string() };
+    svg += &format!("<text x=\"0\" y=\"12\" font-size=\"12\">{}/{}</text>", max, per);
+    for (color, values) in series.iter() {
+        let points: Vec<String> = values.iter().enumerate()
+            .map(|(second, value)| format!(
+                "{:.1},{:.1}",
+                second as f64 * width / (length - 1) as f64,
+                20.0 + height - *value as f64 * height / max as f64,
+            ))
+            .collect();
+        svg += &format!("<polyline fill=\"none\" stroke=\"{}\" stroke-width=\"1.5\" points=\"{}\"/>", color, points.join(" "));
+    }
+    svg + "</svg>"
+}
+
+fn sum(values: &[u32], absorbed: &[u32]) -> Vec<u32> {
+    values.iter().zip(absorbed.iter()).map(|(value, absorbed)| value + absorbed).collect()
+}
+
+fn fight_section(index: usize, fight: &Fight, offset: &FixedOffset, bucket: i64) -> Result<String, String> {
+    let title = match &fight.label {
+        Some(label) => format!("{} - {}", index, escape(label)),
+        None => index.to_string(),
+    };
+    let dps = &fight.dps_stats;
+    let heal = &fight.heal_stats;
+
+    Ok(format!(
+        "<section id=\"fight-{}\"><h2>fight {} : {} -> {} ({}s) against {}</h2>\
+        <div class=\"legend\"><span style=\"color: {}\">damage done</span><span style=\"color: {}\">damage taken</span>\
+        <span style=\"color: {}\">healing done</span><span style=\"color: {}\">healing taken</span></div>{}\
+        <div class=\"charts\">{}{}{}{}{}{}</div></section>\n",
+        index,
+        title,
+        time(fight.time.start, offset, "%H:%M:%S")?,
+        time(fight.time.end, offset, "%H:%M:%S")?,
+        fight.duration(),
+        escape(&fight.opponent.join(", ")),
+        DAMAGE_DONE, DAMAGE_TAKEN, HEALING_DONE, HEALING_TAKEN,
+        timeline(&[
+            (DAMAGE_DONE, sum(&dps.emit_by_seconds, &dps.emit_by_seconds_absorbed)),
+            (DAMAGE_TAKEN, sum(&dps.received_by_seconds, &dps.received_by_seconds_absorbed)),
+            (HEALING_DONE, sum(&heal.emit_by_seconds, &heal.emit_by_seconds_absorbed)),
+            (HEALING_TAKEN, sum(&heal.received_by_seconds, &heal.received_by_seconds_absorbed)),
+        ], bucket),
+        bars("damage done by spell", "damage", &dps.emit_by_spell),
+        bars("damage done by kind", "damage", &dps.emit_by_kind),
+        bars("damage done by opponent", "damage", &dps.emit_by_enemy),
+        bars("damage taken by opponent", "damage", &dps.received_by_enemy),
+        bars("healing done by ally", "heal", &heal.emit_by_ally),
+        bars("healing taken by ally", "heal", &heal.received_by_ally),
+    ))
+}
+
+fn fights_table(fights: &[Fight], offset: &FixedOffset) -> Result<String, String> {
+    let mut html = "<table><tr><th>fight</th><th>start</th><th>duration</th><th>damage done</th><th>damage/s</th>\
+        <th>damage taken</th><th>healing done</th><th>healing taken</th><th>opponents</th></tr>".to_string();
+
+    for (index, fight) in fights.iter().enumerate() {
+        html += &format!(
+            "<tr><td><a href=\"#fight-{}\">{}</a></td><td>{}</td><td>{}s</td><td>{}</td><td>{}</td><td>{}</td><td>{}</td><td>{}</td><td class=\"name\">{}</td></tr>",
+            index,
+            escape(fight.label.as_deref().unwrap_or(&index.to_string())),
+            time(fight.time.start, offset, "%H:%M:%S")?,
+            fight.duration(),
+            fight.damage_done(),
+            fight.damage_done() / fight.duration().max(1) as u64,
+            fight.damage_taken(),
+            fight.healing_done(),
+            fight.healing_taken(),
+            escape(&fight.opponent.join(", ")),
+        );
+    }
+    Ok(html + "</table>")
+}
+
+/// A single HTML file showing the export without any network access, charts drawn in SVG,
+/// the export itself embedded as JSON in the `crowfall-data` script for whoever wants it back.
+/// Dates are written in the `timezone` of the options and the charts use their `bucket`, as the export was made.
+pub fn html_report(data: &ExportedData, options: &ParseOptions) -> Result<String, String> {
+    let offset = &options.offset()?;
+    let date = match data.fights.first() {
+        Some(fight) => time(fight.time.start, offset, "%Y-%m-%d")?,
+        None => String::new(),
+    };
+
+    // a `</script>` inside a name must not end the script
+    let json = serde_json::to_string(data).unwrap().replace("</", "<\\/");
+
+    let mut html = format!(
+        "<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"UTF-8\">\n<title>Crowfall combat log {}</title>\n<style>{}</style>\n</head>\n<body>\n",
+        date, STYLE,
+    );
+    html += &format!("<h1>Crowfall combat log {}</h1>\n", date);
+    html += &format!("<section><h2>{} fights</h2>{}</section>\n", data.fights.len(), fights_table(&data.fights, offset)?);
+    html += &format!(
+        "<section><h2>whole log</h2><div class=\"charts\">{}{}{}{}</div></section>\n",
+        bars("damage done by spell", "damage", &data.dps_stats.emit_by_spell),
+        bars("damage taken by kind", "damage", &data.dps_stats.received_by_kind),
+        bars("healing done by spell", "heal", &data.heal_stats.emit_by_spell),
+        bars("healing taken by ally", "heal", &data.heal_stats.received_by_ally),
+    );
+    for (index, fight) in data.fights.iter().enumerate() {
+        html += &fight_section(index, fight, offset, options.bucket)?;
+    }
+    html += &format!("<script type=\"application/json\" id=\"crowfall-data\">{}</script>\n</body>\n</html>\n", json);
+
+    Ok(html)
+}
+
+#[cfg(test)]
+mod tests {
+    // Note this useful idiom: importing names from outer (for mod tests) scope.
+    use super::*;
+    use crate::parse_rust;
+
+    #[test]
+    fn assert_html_report() {
+        let contents = std::fs::read_to_string("./fixtures/file1.txt").unwrap();
+        let data = parse_rust(&contents, &ParseOptions::default());
+        let html = html_report(&data, &ParseOptions::default()).unwrap();
+
+        assert!(html.starts_with("<!DOCTYPE html>"));
+        assert!(!html.contains("http://") && !html.contains("https://") && !html.contains("<script src"));
+        assert_eq!(html.matches("<section id=\"fight-").count(), data.fights.len());
+        assert!(html.contains("<title>Crowfall combat log 2021-03-31</title>"));
+
+        let start = html.find("id=\"crowfall-data\">").unwrap() + "id=\"crowfall-data\">".len();
+        let end = html[start..].find("</script>").unwrap() + start;
+        let embedded: ExportedData = serde_json::from_str(&html[start..end]).unwrap();
+        assert_eq!(embedded.fights.len(), data.fights.len());
+    }
+
+    #[test]
+    fn assert_html_report_bucket_and_dates() {
+        let contents = std::fs::read_to_string("./fixtures/file1.txt").unwrap();
+        let options = ParseOptions { bucket: 10, ..Default::default() };
+        let mut data = parse_rust(&contents, &options);
+
+        let html = html_report(&data, &options).unwrap();
+        assert!(html.contains("/10s</text>"));
+        assert!(!html.contains("/s</text>"));
+
+        // an export given back by the page may hold any number
+        data.fights[0].time.start = i64::MAX;
+        assert!(html_report(&data, &options).is_err());
+    }
+
+    #[test]
+    fn assert_html_report_escape() {
+        let mut by_name = HashMap::new();
+        by_name.insert("<b>Cleric</b>".to_string(), 10);
+
+        assert!(bars("damage", "damage", &by_name).contains("&lt;b&gt;Cleric&lt;/b&gt;"));
+    }
+}
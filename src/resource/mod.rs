@@ -0,0 +1,96 @@
+use chrono::prelude::*;
+use std::collections::HashMap;
+use serde::{Serialize, Deserialize};
+use schemars::JsonSchema;
+use crate::intern::{Interner, Symbol};
+
+#[derive(Debug, PartialEq)]
+pub struct Resource {
+    pub date: DateTime<FixedOffset>,
+    pub emitter: Symbol,
+    pub spell: Symbol,
+    pub amount: u32,
+    pub kind: Symbol,
+    pub drained: bool,
+}
+
+#[derive(Debug, PartialEq, Clone, Default)]
+#[derive(Serialize, Deserialize, JsonSchema)]
+pub struct ResourceStats {
+    pub restored_by_kind: HashMap<String, u32>,
+    pub drained_by_kind: HashMap<String, u32>,
+}
+
+pub fn stats_resource(list: &[Resource], names: &Interner, start: Option<i64>, end: Option<i64>) -> ResourceStats {
+    let mut restored_by_kind = HashMap::new();
+    let mut drained_by_kind = HashMap::new();
+
+    for resource in list.iter() {
+        if resource.date.timestamp() < start.unwrap_or(0) || resource.date.timestamp() > end.unwrap_or(i64::MAX) {
+            continue;
+        }
+
+        let by_kind = if resource.drained { &mut drained_by_kind } else { &mut restored_by_kind };
+        *by_kind.entry(names.resolve(resource.kind).to_string()).or_insert(0) += resource.amount;
+    }
+
+    ResourceStats {
+        restored_by_kind,
+        drained_by_kind,
+    }
+}
+
+#[cfg(test)]
+mod stats_tests {
+    // Note this useful idiom: importing names from outer (for mod tests) scope.
+    use super::*;
+
+    #[test]
+    fn assert_resource_by_kind() {
+        let mut names = Interner::new();
+        let list = vec![
+            Resource {
+                date: DateTime::parse_from_rfc3339("2021-03-17T20:40:45.111Z").unwrap(),
+                emitter: names.intern("Your"),
+                spell: names.intern("Essence Burn"),
+                amount: 250,
+                kind: names.intern("essence"),
+                drained: false,
+            },
+            Resource {
+                date: DateTime::parse_from_rfc3339("2021-03-17T20:40:46.111Z").unwrap(),
+                emitter: names.intern("Your"),
+                spell: names.intern("Essence Burn"),
+                amount: 100,
+                kind: names.intern("essence"),
+                drained: true,
+            },
+            Resource {
+                date: DateTime::parse_from_rfc3339("2021-03-17T20:40:47.111Z").unwrap(),
+                emitter: names.intern("Your"),
+                spell: names.intern("Essence Burn"),
+                amount: 50,
+                kind: names.intern("essence"),
+                drained: false,
+            },
+            Resource {
+                date: DateTime::parse_from_rfc3339("2021-03-17T21:40:47.111Z").unwrap(),
+                emitter: names.intern("Your"),
+                spell: names.intern("Essence Burn"),
+                amount: 50,
+                kind: names.intern("essence"),
+                drained: false,
+            },
+        ];
+
+        let stats = stats_resource(
+            &list,
+            &names,
+            Some(DateTime::parse_from_rfc3339("2021-03-17T20:40:00.111Z").unwrap().timestamp()),
+            Some(DateTime::parse_from_rfc3339("2021-03-17T20:42:00.111Z").unwrap().timestamp()),
+        );
+
+        assert_eq!(stats.restored_by_kind["essence"], 300);
+        assert_eq!(stats.drained_by_kind["essence"], 100);
+    }
+}
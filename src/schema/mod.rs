@@ -0,0 +1,161 @@
+use schemars::schema::{InstanceType, RootSchema, Schema, SchemaObject, SingleOrVec};
+use schemars::schema_for;
+use std::collections::BTreeMap;
+use crate::compare::FightComparison;
+use crate::options::ParseOptions;
+use crate::progression::{CharacterSession, Progression};
+use crate::query::{EventPage, Filter, QueryResult};
+use crate::session::Progress;
+use crate::ExportedData;
+
+/// The JSON Schema of everything `parse` and `Session` return.
+pub fn schema() -> RootSchema {
+    schema_for!(ExportedData)
+}
+
+/// TypeScript interfaces for the export, the query results, the event pages, the progression reports, the fight comparisons and what the page passes in.
+pub fn typescript() -> String {
+    let mut definitions = BTreeMap::new();
+
+    for root in [schema(), schema_for!(QueryResult), schema_for!(EventPage), schema_for!(ParseOptions), schema_for!(Filter), schema_for!(Progress), schema_for!(CharacterSession), schema_for!(Progression), schema_for!(FightComparison)] {
+        let name = root.schema.metadata.as_ref().and_then(|metadata| metadata.title.clone()).unwrap();
+        definitions.extend(root.definitions);
+        definitions.insert(name, Schema::Object(root.schema));
+    }
+
+    let mut ts = String::new();
+    for (name, definition) in definitions.iter() {
+        ts += &declaration(name, definition);
+    }
+
+    ts
+}
+
+fn description(schema: &SchemaObject, indent: &str) -> String {
+    match schema.metadata.as_ref().and_then(|metadata| metadata.description.as_ref()) {
+        Some(description) => format!("{}/** {} */\n", indent, description.replace('\n', " ")),
+        None => String::new(),
+    }
+}
+
+fn declaration(name: &str, definition: &Schema) -> String {
+    let schema = match definition {
+        Schema::Object(schema) => schema,
+        Schema::Bool(_) => return format!("export type {} = any;\n\n", name),
+    };
+
+    let object = match &schema.object {
+        Some(object) if !object.properties.is_empty() => object,
+        _ => return format!("{}export type {} = {};\n\n", description(schema, ""), name, type_of(definition)),
+    };
+
+    let mut ts = format!("{}export interface {} {{\n", description(schema, ""), name);
+    for (field, property) in object.properties.iter() {
+        if let Schema::Object(property) = property {
+            ts += &description(property, "    ");
+        }
+        let optional = if object.required.contains(field) { "" } else { "?" };
+        ts += &format!("    {}{}: {};\n", field, optional, type_of(property));
+    }
+    ts += "}\n\n";
+
+    ts
+}
+
+pub(crate) fn type_of(schema: &Schema) -> String {
+    let schema = match schema {
+        Schema::Object(schema) => schema,
+        Schema::Bool(_) => return "any".to_string(),
+    };
+
+    if let Some(reference) = &schema.reference {
+        return reference.trim_start_matches("#/definitions/").to_string();
+    }
+
+    if let Some(values) = &schema.enum_values {
+        let values: Vec<String> = values.iter().map(|value| value.to_string()).collect();
+        return values.join(" | ");
+    }
+
+    if let Some(subschemas) = &schema.subschemas {
+        let alternatives = subschemas.any_of.as_ref()
+            .or(subschemas.one_of.as_ref())
+            .or(subschemas.all_of.as_ref());
+        if let Some(alternatives) = alternatives {
+            let types: Vec<String> = alternatives.iter().map(type_of).collect();
+            return types.join(" | ");
+        }
+    }
+
+    match &schema.instance_type {
+        Some(SingleOrVec::Single(instance)) => instance_type(instance, schema),
+        Some(SingleOrVec::Vec(instances)) => {
+            let types: Vec<String> = instances.iter().map(|instance| instance_type(instance, schema)).collect();
+            types.join(" | ")
+        }
+        None => "any".to_string(),
+    }
+}
+
+fn instance_type(instance: &InstanceType, schema: &SchemaObject) -> String {
+    match instance {
+        InstanceType::Null => "null".to_string(),
+        InstanceType::Boolean => "boolean".to_string(),
+        InstanceType::Integer | InstanceType::Number => "number".to_string(),
+        InstanceType::String => "string".to_string(),
+        InstanceType::Array => match schema.array.as_ref().and_then(|array| array.items.as_ref()) {
+            Some(SingleOrVec::Single(item)) => {
+                let item = type_of(item);
+                if item.contains(' ') { format!("({})[]", item) } else { format!("{}[]", item) }
+            }
+            _ => "any[]".to_string(),
+        },
+        InstanceType::Object => match schema.object.as_ref() {
+            Some(object) if !object.properties.is_empty() => {
+                let fields: Vec<String> = object.properties.iter()
+                    .map(|(field, property)| {
+                        let optional = if object.required.contains(field) { "" } else { "?" };
+                        format!("{}{}: {}", field, optional, type_of(property))
+                    })
+                    .collect();
+                format!("{{ {} }}", fields.join("; "))
+            }
+            Some(object) if object.additional_properties.is_some() => format!("Record<string, {}>", type_of(object.additional_properties.as_ref().unwrap())),
+            _ => "Record<string, any>".to_string(),
+        },
+    }
+}
+
+/// Compare `actual` to the file at `path`, or write it when `UPDATE_SNAPSHOTS` is set.
+#[cfg(test)]
+pub(crate) fn assert_snapshot(path: &str, actual: &str) {
+    if std::env::var("UPDATE_SNAPSHOTS").is_ok() {
+        std::fs::write(path, actual).unwrap();
+    }
+
+    let expected = std::fs::read_to_string(path).unwrap();
+    assert!(expected == actual, "{} is out of date, run the tests with UPDATE_SNAPSHOTS=1 and review the diff", path);
+}
+
+#[cfg(test)]
+mod tests {
+    // Note this useful idiom: importing names from outer (for mod tests) scope.
+    use super::*;
+
+    #[test]
+    fn assert_schema_file() {
+        assert_snapshot("./schema/exported_data.json", &(serde_json::to_string_pretty(&schema()).unwrap() + "\n"));
+    }
+
+    #[test]
+    fn assert_typescript_file() {
+        let ts = typescript();
+
+        assert!(ts.contains("export interface ExportedData {\n"));
+        assert!(ts.contains("    label?: string | null;\n"));
+        assert!(ts.contains("    emit_by_enemy: Record<string, number>;\n"));
+        assert!(ts.contains("export type EventType = \"damage\" | \"heal\";\n"));
+        assert!(ts.contains("{ length: number; name: \"window\" }"));
+        assert_snapshot("./schema/exported_data.d.ts", &ts);
+    }
+}
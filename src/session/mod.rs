@@ -0,0 +1,702 @@
+use chrono::prelude::{DateTime, FixedOffset, TimeZone};
+use serde::{Serialize, Deserialize};
+use schemars::JsonSchema;
+use crate::compare::{compare, FightComparison};
+use crate::custom::*;
+use crate::dps::*;
+use crate::food::*;
+use crate::heal::*;
+use crate::intern::Interner;
+use crate::options::ParseOptions;
+use crate::query::{query, timeline, EventPage, Filter, QueryResult, RawEvent};
+use crate::resource::*;
+use crate::split::{Activity, FightSplitter, FightTimer};
+use crate::tokenizer::*;
+use crate::unknown::*;
+use crate::{between, Data, ExportedData, Fight, SCHEMA_VERSION};
+
+/// How far a parse went, `bytes` are read from the start of the text.
+#[derive(Debug, PartialEq, Clone, Default)]
+#[derive(Serialize, Deserialize, JsonSchema)]
+pub struct Progress {
+    pub lines: usize,
+    pub bytes: usize,
+    pub events: usize,
+    pub total_bytes: usize,
+}
+
+/// The fight going on and the last one over, what a live view shows.
+#[derive(Debug, Serialize)]
+pub struct Live<'a> {
+    pub fights: usize,
+    pub current: Option<&'a Fight>,
+    pub last: Option<&'a Fight>,
+}
+
+/// A parsed log kept in memory, its fights can be cut again or edited without reading the text twice.
+pub struct Session {
+    data: Data,
+    activities: Vec<Activity>,
+    errors: Vec<String>,
+    warnings: Vec<String>,
+    fights: Vec<Fight>,
+    options: ParseOptions,
+    pending: String,
+    /// The fights ended before are over, `update` leaves them be.
+    settled: Option<DateTime<FixedOffset>>,
+}
+
+impl Session {
+    /// A session without any line yet, fed by `append`.
+    pub fn new(options: &ParseOptions) -> Session {
+        let (patterns, errors) = compile_patterns(&options.patterns);
+
+        Session {
+            data: Data {
+                names: Interner::new(),
+                dps: Default::default(),
+                heal: Default::default(),
+                food: Default::default(),
+                resource: Default::default(),
+                unknown: Default::default(),
+                custom: Default::default(),
+                patterns,
+                self_name: options.self_name.clone(),
+            },
+            activities: vec![],
+            errors,
+            warnings: vec![],
+            fights: vec![],
+            options: options.clone(),
+            pending: String::new(),
+            settled: None,
+        }
+    }
+
+    pub fn parse(contents: &str, options: &ParseOptions) -> Session {
+        Session::parse_with_progress(contents, options, |_| true).unwrap()
+    }
+
+    /// Parse and give `progress` the progress every `options.progress_every` lines and at the end,
+    /// the parse stops and gives `None` as soon as it returns `false`.
+    pub fn parse_with_progress(contents: &str, options: &ParseOptions, mut progress: impl FnMut(&Progress) -> bool) -> Option<Session> {
+        let mut session = Session::new(options);
+        let mut current = Progress { total_bytes: contents.len(), ..Default::default() };
+        let every = options.progress_every.max(1);
+
+        for line in contents.lines() {
+            current.lines += 1;
+            if current.lines.is_multiple_of(every) {
+                current.bytes = line.as_ptr() as usize - contents.as_ptr() as usize;
+                current.events = session.activities.len();
+                if !progress(&current) {
+                    return None;
+                }
+            }
+
+            session.parse_line(line);
+        }
+
+        current.bytes = contents.len();
+        current.events = session.activities.len();
+        if !progress(&current) {
+            return None;
+        }
+
+        session.data.sort();
+        Some(session)
+    }
+
+    /// Parse text written at the end of the log since the last call, a last line without its end waits for the next one.
+    /// The fights are not split again, call `update` or `resplit` for it.
+    pub fn append(&mut self, text: &str) {
+        let lengths = self.data.lengths();
+        self.pending.push_str(text);
+
+        let complete = match self.pending.rfind('\n') {
+            Some(end) => end + 1,
+            None => return,
+        };
+        let pending = self.pending.split_off(complete);
+        let lines = std::mem::replace(&mut self.pending, pending);
+
+        for line in lines.lines() {
+            self.parse_line(line);
+        }
+
+        self.data.sort_after(lengths);
+    }
+
+    /// Split again the fights that may still change after an `append`, the ones over are kept as they are,
+    /// so following a log costs the lines added and the fight going on, not the whole log.
+    /// Splitters without `settled` cut everything again, edits of the fights still open are lost.
+    pub fn update(&mut self) {
+        let splitter = self.options.splitter();
+        // a log is written in order, so are its activities
+        let from = self.settled.map_or(0, |date| self.activities.partition_point(|activity| activity.date < date));
+        let open = &self.activities[from..];
+
+        let fights: Vec<Fight> = splitter.split(open).into_iter()
+            .map(|timer| self.fight(timer, None))
+            .collect();
+        let settled = splitter.settled(open).or(self.settled);
+
+        let over = self.settled.map_or(0, |date| self.fights.partition_point(|fight| fight.time.end < date.timestamp()));
+        self.fights.truncate(over);
+        self.fights.extend(fights);
+        self.settled = settled;
+    }
+
+    /// Date of the last line understood, in seconds.
+    pub fn last_activity(&self) -> Option<i64> {
+        self.activities.last().map(|activity| activity.date.timestamp())
+    }
+
+    fn parse_line(&mut self, line: &str) {
+        let (date, row) = match split_line(line) {
+            Some(split) => split,
+            None => return,
+        };
+
+        let activity = match parse_timestamp(date) {
+            Some(d) => self.data.parse_row(row, d),
+            None => {
+                // a date cut by a rotation of the log is an unknown line like any other, dated as the line before it
+                let before = self.activities.last().map_or_else(|| FixedOffset::east(0).timestamp(0, 0), |activity| activity.date);
+                self.data.unknown.push(parse_unknown(row, before));
+                None
+            }
+        };
+
+        if let Some(activity) = activity {
+            self.activities.push(activity);
+        } else if self.options.strict {
+            self.errors.push(row.to_string());
+        } else {
+            self.warnings.push(row.to_string());
+        }
+    }
+
+    /// Replace every fight by the ones found by `splitter`, labels are lost.
+    pub fn split(&mut self, splitter: &dyn FightSplitter) {
+        self.fights = splitter.split(&self.activities).into_iter()
+            .map(|timer| self.fight(timer, None))
+            .collect();
+        self.settled = None;
+    }
+
+    pub fn options(&self) -> &ParseOptions {
+        &self.options
+    }
+
+    /// Change how the next fights are split and exported, the patterns, strictness and self name only apply to a new parse.
+    pub fn set_options(&mut self, options: ParseOptions) {
+        self.options = options;
+    }
+
+    /// Split the fights again with the strategy of the options.
+    pub fn resplit(&mut self) {
+        let splitter = self.options.splitter();
+        self.split(&splitter);
+    }
+
+    /// Keep the first `page_size` events in each fight computed from now on, `None` to leave them out.
+    pub fn set_timeline(&mut self, page_size: Option<usize>) {
+        self.options.timeline = page_size;
+    }
+
+    /// The events of the fight `index` from `offset`, at most `limit` of them.
+    pub fn timeline(&self, index: usize, offset: usize, limit: usize) -> Result<EventPage, String> {
+        let current = self.fights.get(index).ok_or(format!("no fight {}", index))?;
+        let (start, end) = (current.time.start, current.time.end);
+        let dps = between(&self.data.dps, start, end, |dps| dps.date.timestamp());
+        let heal = between(&self.data.heal, start, end, |heal| heal.date.timestamp());
+
+        Ok(timeline(dps, heal, &self.data.names, offset, limit))
+    }
+
+    /// Every hit and heal of the fight `index`, or of the whole log for `None`.
+    pub fn events(&self, index: Option<usize>) -> Result<Vec<RawEvent>, String> {
+        let page = match index {
+            Some(index) => self.timeline(index, 0, usize::MAX)?,
+            None => timeline(&self.data.dps, &self.data.heal, &self.data.names, 0, usize::MAX),
+        };
+
+        Ok(page.events)
+    }
+
+    pub fn fights(&self) -> &[Fight] {
+        &self.fights
+    }
+
+    /// The fights at `now` in seconds, the last one is still going on until quiet for `time_between`.
+    pub fn live(&self, now: i64) -> Live<'_> {
+        let (current, last) = match self.fights.split_last() {
+            Some((last, before)) if now - last.time.end <= self.options.time_between => (Some(last), before.last()),
+            Some((last, _)) => (None, Some(last)),
+            None => (None, None),
+        };
+
+        Live { fights: self.fights.len(), current, last }
+    }
+
+    /// The stats of any time range of the session, from `start` to `end` seconds included.
+    pub fn fight(&self, timer: FightTimer, label: Option<String>) -> Fight {
+        let data = &self.data;
+        let sections = &self.options.sections;
+        let (start, end) = (Some(timer.start), Some(timer.end));
+
+        let dps = between(&data.dps, timer.start, timer.end, |dps| dps.date.timestamp());
+        let heal = between(&data.heal, timer.start, timer.end, |heal| heal.date.timestamp());
+        let custom = between(&data.custom, timer.start, timer.end, |custom| custom.date.timestamp());
+        let resource = between(&data.resource, timer.start, timer.end, |resource| resource.date.timestamp());
+
+        // the opponents are always listed, they name the fight
+        let (mut dps_stats, mut opponent) = stats_dps(dps, &data.names, start, end);
+        let (mut heal_stats, mut opponent_heal) = stats_heal(heal, &data.names, start, end);
+        opponent.append(&mut opponent_heal);
+
+        opponent.sort();
+        opponent.dedup();
+
+        let width = self.options.bucket;
+        if sections.dps {
+            for seconds in [&mut dps_stats.emit_by_seconds, &mut dps_stats.emit_by_seconds_absorbed, &mut dps_stats.received_by_seconds, &mut dps_stats.received_by_seconds_absorbed] {
+                bucket(seconds, width);
+            }
+        } else {
+            dps_stats = Default::default();
+        }
+        if sections.heal {
+            for seconds in [&mut heal_stats.emit_by_seconds, &mut heal_stats.emit_by_seconds_absorbed, &mut heal_stats.received_by_seconds, &mut heal_stats.received_by_seconds_absorbed] {
+                bucket(seconds, width);
+            }
+        } else {
+            heal_stats = Default::default();
+        }
+
+        let custom_stats = if sections.custom { stats_custom(custom, start, end) } else { Default::default() };
+        let resource_stats = if sections.resource { stats_resource(resource, &data.names, start, end) } else { Default::default() };
+        let events = self.options.timeline.map(|page_size| timeline(dps, heal, &data.names, 0, page_size));
+
+        Fight {
+            label,
+            time: timer,
+            dps_stats,
+            heal_stats,
+            resource_stats,
+            custom_stats,
+            opponent,
+            events,
+        }
+    }
+
+    /// Merge the fight `index` with the one after it.
+    pub fn merge(&mut self, index: usize) -> Result<(), String> {
+        if index + 1 >= self.fights.len() {
+            return Err(format!("no fight after fight {}", index));
+        }
+
+        let next = self.fights.remove(index + 1);
+        let current = &self.fights[index];
+        let timer = FightTimer {
+            start: current.time.start.min(next.time.start),
+            end: current.time.end.max(next.time.end),
+        };
+        let label = current.label.clone().or(next.label);
+
+        self.fights[index] = self.fight(timer, label);
+        Ok(())
+    }
+
+    /// Cut the fight `index` in two, the second one starting at `at`.
+    pub fn split_fight(&mut self, index: usize, at: i64) -> Result<(), String> {
+        let current = self.fights.get(index).ok_or(format!("no fight {}", index))?;
+        if at <= current.time.start || at > current.time.end {
+            return Err(format!("{} is not inside fight {}", at, index));
+        }
+
+        let first = FightTimer { start: current.time.start, end: at - 1 };
+        let second = FightTimer { start: at, end: current.time.end };
+        let label = current.label.clone();
+
+        self.fights[index] = self.fight(first, label);
+        let second = self.fight(second, None);
+        self.fights.insert(index + 1, second);
+        Ok(())
+    }
+
+    /// Shrink the fight `index` to the range from `start` to `end`.
+    pub fn trim(&mut self, index: usize, start: i64, end: i64) -> Result<(), String> {
+        let current = self.fights.get(index).ok_or(format!("no fight {}", index))?;
+        if start > end || start < current.time.start || end > current.time.end {
+            return Err(format!("{} -> {} is not inside fight {}", start, end, index));
+        }
+
+        let label = current.label.clone();
+        self.fights[index] = self.fight(FightTimer { start, end }, label);
+        Ok(())
+    }
+
+    /// The stats from `start` to `end` seconds included as a fight, the range cut to the dates of the log.
+    /// A range ending before it starts or outside the log is an error.
+    pub fn stats(&self, start: i64, end: i64) -> Result<Fight, String> {
+        if start > end {
+            return Err(format!("{} -> {} ends before it starts", start, end));
+        }
+
+        let dates = self.activities.iter().map(|activity| activity.date.timestamp());
+        match (dates.clone().min(), dates.max()) {
+            (Some(first), Some(last)) if start <= last && end >= first => {
+                Ok(self.fight(FightTimer { start: start.max(first), end: end.min(last) }, None))
+            }
+            _ => Err(format!("{} -> {} is outside the log", start, end)),
+        }
+    }
+
+    pub fn rename(&mut self, index: usize, label: &str) -> Result<(), String> {
+        let current = self.fights.get_mut(index).ok_or(format!("no fight {}", index))?;
+        current.label = if label.is_empty() { None } else { Some(label.to_string()) };
+        Ok(())
+    }
+
+    pub fn compare(&self, first: usize, second: usize) -> Result<FightComparison, String> {
+        let fight = |index: usize| self.fights.get(index).ok_or(format!("no fight {}", index));
+        Ok(compare(fight(first)?, fight(second)?))
+    }
+
+    pub fn query(&self, filter: &Filter) -> QueryResult {
+        let start = filter.start.unwrap_or(0);
+        let end = filter.end.unwrap_or(i64::MAX);
+        let dps = between(&self.data.dps, start, end, |dps| dps.date.timestamp());
+        let heal = between(&self.data.heal, start, end, |heal| heal.date.timestamp());
+
+        query(dps, heal, &self.data.names, filter)
+    }
+
+    pub fn export(&self) -> ExportedData {
+        let data = &self.data;
+        let sections = &self.options.sections;
+
+        // every pattern of the log is listed, one that never matched counts 0
+        let mut custom_stats = if sections.custom { stats_custom(&data.custom, None, None) } else { Default::default() };
+        if sections.custom {
+            for pattern in data.patterns.iter() {
+                custom_stats.count_by_name.entry(pattern.name.clone()).or_insert(0);
+                custom_stats.amount_by_name.entry(pattern.name.clone()).or_insert(0);
+            }
+        }
+
+        ExportedData {
+            schema_version: SCHEMA_VERSION,
+            dps_stats: if sections.dps { stats_dps(&data.dps, &data.names, None, None).0 } else { Default::default() },
+            heal_stats: if sections.heal { stats_heal(&data.heal, &data.names, None, None).0 } else { Default::default() },
+            food_stats: if sections.food { stats_food(&data.food) } else { Default::default() },
+            resource_stats: if sections.resource { stats_resource(&data.resource, &data.names, None, None) } else { Default::default() },
+            custom_stats,
+            errors: self.errors.clone(),
+            warnings: self.warnings.clone(),
+            unknown: if sections.unknown { data.unknown.clone() } else { vec![] },
+            unknown_shapes: if sections.unknown { stats_unknown(&data.unknown) } else { Default::default() },
+            fights: if sections.fights { self.fights.clone() } else { vec![] },
+        }
+    }
+}
+
+/// Sum a per second array by periods of `width` seconds.
+fn bucket(seconds: &mut Vec<u32>, width: i64) {
+    if width > 1 {
+        *seconds = seconds.chunks(width as usize).map(|chunk| chunk.iter().sum()).collect();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    // Note this useful idiom: importing names from outer (for mod tests) scope.
+    use super::*;
+    use crate::options::Sections;
+    use crate::split::GapSplitter;
+
+    const LOG: &str = "2021-03-31T04:36:00.225Z INFO    COMBAT    - Combat _||_ Event=[Your Static Bolt hit Cleric Defender for 100 Nature damage.]
+2021-03-31T04:36:10.225Z INFO    COMBAT    - Combat _||_ Event=[Cleric Defender Hammer hit You for 38 Fire damage.]
+2021-03-31T04:37:00.225Z INFO    COMBAT    - Combat _||_ Event=[Your Static Bolt hit Ranger Defender for 200 Nature damage.]
+2021-03-31T04:37:20.225Z INFO    COMBAT    - Combat _||_ Event=[Your Static Bolt hit Ranger Defender for 300 Nature damage.]";
+
+    fn session() -> Session {
+        let mut session = Session::parse(LOG, &ParseOptions::default());
+        session.split(&GapSplitter { time_between: 30, minimum_time: 0 });
+        session
+    }
+
+    #[test]
+    fn assert_session_split() {
+        let session = session();
+        assert_eq!(session.fights().len(), 2);
+        assert_eq!(session.fights()[1].dps_stats.emit_by_enemy["Ranger Defender"], 500);
+    }
+
+    #[test]
+    fn assert_live() {
+        let session = session();
+        let end = session.fights()[1].time.end;
+
+        let live = session.live(end + 10);
+        assert_eq!(live.current.map(|fight| fight.time.end), Some(end));
+        assert_eq!(live.last.map(|fight| fight.time.start), Some(session.fights()[0].time.start));
+
+        let live = session.live(end + 31);
+        assert!(live.current.is_none());
+        assert_eq!(live.last.map(|fight| fight.time.end), Some(end));
+    }
+
+    #[test]
+    fn assert_merge() {
+        let mut session = session();
+        session.rename(1, "second pull").unwrap();
+        session.merge(0).unwrap();
+
+        assert_eq!(session.fights().len(), 1);
+        let fight = &session.fights()[0];
+        assert_eq!(fight.label, Some("second pull".to_string()));
+        assert_eq!(fight.dps_stats.emit_by_seconds.len(), 81);
+        assert_eq!(fight.opponent, vec!["cleric", "cleric defender", "ranger defender"]);
+        assert!(session.merge(0).is_err());
+    }
+
+    #[test]
+    fn assert_split_and_trim() {
+        let mut session = session();
+        let start = session.fights()[1].time.start;
+
+        session.split_fight(1, start + 10).unwrap();
+        assert_eq!(session.fights().len(), 3);
+        assert_eq!(session.fights()[1].dps_stats.emit_by_enemy["Ranger Defender"], 200);
+        assert_eq!(session.fights()[2].dps_stats.emit_by_enemy["Ranger Defender"], 300);
+        assert!(session.split_fight(1, start).is_err());
+
+        let first = session.fights()[0].time.clone();
+        session.trim(0, first.start, first.start + 5).unwrap();
+        assert_eq!(session.fights()[0].dps_stats.received_by_enemy.len(), 0);
+        assert!(session.trim(0, first.start, first.end + 100).is_err());
+    }
+
+    #[test]
+    fn assert_session_stats() {
+        let session = session();
+        let (start, end) = (session.fights()[0].time.start, session.fights()[1].time.end);
+
+        let fight = session.stats(0, i64::MAX).unwrap();
+        assert_eq!((fight.time.start, fight.time.end), (start, end));
+        assert_eq!(fight.dps_stats.emit_by_seconds.len(), 81);
+        assert_eq!(fight.damage_done(), 600);
+
+        assert!(session.stats(end, start).is_err());
+        assert!(session.stats(end + 1, i64::MAX).is_err());
+        assert!(Session::new(&ParseOptions::default()).stats(0, 10).is_err());
+    }
+
+    #[test]
+    fn assert_session_query() {
+        let session = session();
+        let start = session.fights()[0].time.start;
+
+        let result = session.query(&Filter {
+            start: Some(start),
+            end: Some(start + 60),
+            receiver: Some("You".to_string()),
+            ..Default::default()
+        });
+        assert_eq!(result.events.len(), 1);
+        assert_eq!(result.events[0].kind, "Fire");
+        assert_eq!(result.dps_stats.received_by_enemy["Cleric"], 38);
+    }
+
+    #[test]
+    fn assert_session_timeline() {
+        let mut session = session();
+        assert_eq!(session.fights()[1].events, None);
+
+        session.set_timeline(Some(1));
+        session.split(&GapSplitter { time_between: 30, minimum_time: 0 });
+        let first = session.fights()[1].events.clone().unwrap();
+        assert_eq!(first.total, 2);
+        assert_eq!(first.events[0].amount, 200);
+        assert_eq!(first.events[0].timestamp % 1000, 225);
+
+        let next = session.timeline(1, 1, 1).unwrap();
+        assert_eq!(next.events[0].amount, 300);
+        assert!(session.timeline(5, 0, 1).is_err());
+    }
+
+    #[test]
+    fn assert_session_csv() {
+        let session = session();
+
+        let events = crate::csv::events_csv(&session.events(Some(1)).unwrap(), &session.options().offset().unwrap()).unwrap();
+        assert_eq!(events.lines().count(), 3);
+        assert_eq!(crate::csv::events_csv(&session.events(None).unwrap(), &session.options().offset().unwrap()).unwrap().lines().count(), 5);
+
+        let fights = crate::csv::fights_csv(session.fights(), &session.options().offset().unwrap()).unwrap();
+        let lines: Vec<&str> = fights.lines().collect();
+        assert_eq!(lines.len(), 3);
+        assert_eq!(lines[1], "0,,2021-03-31T04:36:00.000Z,2021-03-31T04:36:10.000Z,10,100,38,0,0,cleric; cleric defender");
+    }
+
+    #[test]
+    fn assert_session_options() {
+        let options = ParseOptions {
+            time_between: 60,
+            minimum_time: 0,
+            bucket: 10,
+            sections: Sections { heal: false, unknown: false, ..Default::default() },
+            ..Default::default()
+        };
+        let mut session = Session::parse(LOG, &options);
+        session.resplit();
+
+        assert_eq!(session.fights().len(), 1);
+        let fight = &session.fights()[0];
+        assert_eq!(fight.dps_stats.emit_by_seconds.len(), 9);
+        assert_eq!(fight.dps_stats.emit_by_seconds[6], 200);
+        assert_eq!(fight.dps_stats.emit_by_seconds[8], 300);
+        assert_eq!(fight.heal_stats, Default::default());
+    }
+
+    #[test]
+    fn assert_session_self_name() {
+        let log = "2021-03-31T04:36:00.225Z INFO    COMBAT    - Combat _||_ Event=[Aedius Static Bolt hit Cleric Defender for 100 Nature damage.]
+2021-03-31T04:36:01.225Z INFO    COMBAT    - Combat _||_ Event=[Aedius Static Bolt hit Cleric Defender for 50 Nature damage.]";
+        let options = ParseOptions { self_name: Some("Aedius".to_string()), minimum_time: 0, ..Default::default() };
+        let mut session = Session::parse(log, &options);
+        session.resplit();
+
+        assert_eq!(session.fights().len(), 1);
+        assert_eq!(session.fights()[0].dps_stats.emit_by_enemy["Cleric Defender"], 150);
+    }
+
+    #[test]
+    fn assert_parse_progress() {
+        let options = ParseOptions { progress_every: 2, ..Default::default() };
+        let mut reports = vec![];
+        let session = Session::parse_with_progress(LOG, &options, |progress| {
+            reports.push(progress.clone());
+            true
+        });
+
+        assert!(session.is_some());
+        assert_eq!(reports.len(), 3);
+        assert_eq!(reports[0].lines, 2);
+        assert_eq!(reports[0].events, 1);
+        assert_eq!(reports[2].bytes, LOG.len());
+        assert_eq!(reports[2].events, 4);
+
+        let cancelled = Session::parse_with_progress(LOG, &options, |progress| progress.lines < 4);
+        assert!(cancelled.is_none());
+    }
+
+    #[test]
+    fn assert_append() {
+        let options = ParseOptions { minimum_time: 0, ..Default::default() };
+        let mut session = Session::new(&options);
+        let cut = LOG.find("04:37:00").unwrap() + 10;
+
+        session.append(&LOG[..cut]);
+        session.resplit();
+        assert_eq!(session.fights().len(), 1);
+        assert_eq!(session.last_activity(), Some(session.fights()[0].time.end));
+
+        session.append(&LOG[cut..]);
+        session.resplit();
+        assert_eq!(session.fights().len(), 1);
+
+        session.append("\n");
+        session.resplit();
+        assert_eq!(session.fights().len(), 2);
+        assert_eq!(session.fights()[1].dps_stats.emit_by_enemy["Ranger Defender"], 500);
+    }
+
+    #[test]
+    fn assert_update() {
+        // the fixture holds the same log twice, keep one
+        let contents = std::fs::read_to_string("./fixtures/file1.txt").unwrap();
+        let lines: Vec<&str> = contents.lines().take(3527).collect();
+
+        // short fights are dropped once over, also when a chunk ends on a lone hit
+        for (minimum_time, size) in [(0, 100), (30, 100), (30, 7), (30, 1)] {
+            let options = ParseOptions { minimum_time, ..Default::default() };
+
+            let mut session = Session::new(&options);
+            for chunk in lines.chunks(size) {
+                session.append(&(chunk.join("\n") + "\n"));
+                session.update();
+            }
+            assert!(session.settled.is_some());
+
+            // a lone hit long after the last fight is too short to be one, also once split alone
+            let lone = "2021-03-31T07:00:00.000Z INFO    COMBAT    - Combat _||_ Event=[Your Static Bolt hit Cleric Defender for 100 Nature damage.]
+2021-03-31T07:00:05.000Z INFO    COMBAT    - Combat _||_ Event=[Your Essence Burn drained You for 25 essence.]";
+            for line in lone.lines() {
+                session.append(&(line.to_string() + "\n"));
+                session.update();
+            }
+
+            let mut whole = Session::parse(&(lines.join("\n") + "\n" + lone), &options);
+            whole.resplit();
+            assert_eq!(session.fights().len(), whole.fights().len());
+            for (fight, expected) in session.fights().iter().zip(whole.fights()) {
+                assert_eq!(fight.time, expected.time);
+                assert_eq!(fight.damage_done(), expected.damage_done());
+                assert_eq!(fight.healing_taken(), expected.healing_taken());
+            }
+        }
+    }
+
+    #[test]
+    fn assert_update_keep_over() {
+        let options = ParseOptions { minimum_time: 0, ..Default::default() };
+        let mut session = Session::new(&options);
+        session.append(&(LOG.to_string() + "\n"));
+        session.update();
+        assert_eq!(session.fights().len(), 2);
+
+        // the first fight is over, its label stays while the second one grows
+        session.rename(0, "first pull").unwrap();
+        session.append("2021-03-31T04:37:30.225Z INFO    COMBAT    - Combat _||_ Event=[Your Static Bolt hit Ranger Defender for 50 Nature damage.]\n");
+        session.update();
+        assert_eq!(session.fights()[0].label, Some("first pull".to_string()));
+        assert_eq!(session.fights()[1].dps_stats.emit_by_enemy["Ranger Defender"], 550);
+    }
+
+    #[test]
+    fn assert_append_bad_date() {
+        let mut session = Session::new(&ParseOptions::default());
+        session.append("2021-03-31T04:36:61.225Z INFO    COMBAT    - Combat _||_ Event=[Your Static Bolt hit Cleric Defender for 100 Nature damage.]\n");
+        session.append("2021-03-31T04:3 INFO    COMBAT    - Combat _||_ Event=[Your Static Bolt hit Cleric Defender for 100 Nature damage.]\n");
+
+        assert_eq!(session.last_activity(), None);
+        let export = session.export();
+        assert_eq!(export.warnings.len(), 1);
+        assert_eq!(export.unknown.len(), 1);
+        assert_eq!(export.unknown[0].date, 0);
+        assert_eq!(export.unknown_shapes["Your Static Bolt hit Cleric Defender for # Nature damage."], 1);
+
+        // dated as the line before it
+        session.append(&(LOG.lines().next().unwrap().to_string() + "\n"));
+        session.append("2021-03-31T04:36:61.225Z INFO    COMBAT    - Combat _||_ Event=[You died.]\n");
+        let export = session.export();
+        assert_eq!(export.unknown.len(), 2);
+        assert_eq!(export.unknown[1].date, session.last_activity().unwrap());
+        assert_eq!(export.unknown_shapes["You died."], 1);
+    }
+
+    #[test]
+    fn assert_export_keep_edit() {
+        let mut session = session();
+        session.rename(0, "opening").unwrap();
+
+        let exported = session.export();
+        assert_eq!(exported.fights.len(), 2);
+        assert_eq!(exported.fights[0].label, Some("opening".to_string()));
+        assert_eq!(exported.dps_stats.emit_by_enemy.len(), 2);
+    }
+}
@@ -2,14 +2,201 @@
 use chrono::prelude::{DateTime, FixedOffset};
 use chrono::Duration;
 use serde::{Serialize, Deserialize};
+use schemars::JsonSchema;
+use std::collections::HashSet;
+use crate::intern::Symbol;
 
 
-#[derive(Debug, PartialEq, Serialize, Deserialize, Clone)]
+#[derive(Debug, PartialEq, Serialize, Deserialize, JsonSchema, Clone)]
 pub struct FightTimer {
     pub start:i64,
     pub end:i64
 }
 
+#[derive(Debug, PartialEq, Eq, Hash, Serialize, Deserialize, JsonSchema, Clone, Copy)]
+#[serde(rename_all = "snake_case")]
+pub enum ActivityKind {
+    DamageDealt,
+    DamageReceived,
+    HealDone,
+    HealReceived,
+    Resource,
+    Food,
+    Custom,
+}
+
+/// Only fighting keeps a fight alive, regen ticks and meals do not.
+pub const DEFAULT_FIGHT_KINDS: [ActivityKind; 2] = [ActivityKind::DamageDealt, ActivityKind::DamageReceived];
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub struct Activity {
+    pub date: DateTime<FixedOffset>,
+    pub kind: ActivityKind,
+    pub opponent: Option<Symbol>,
+}
+
+/// Cut the activity of a parse into fights, implement it to add your own segmentation.
+pub trait FightSplitter {
+    fn split(&self, activities: &[Activity]) -> Vec<FightTimer>;
+
+    /// A date splitting the activities in two parts cut into the same fights apart as together,
+    /// whatever comes after them, so the fights of the first part are over. `None` when there is none.
+    fn settled(&self, _activities: &[Activity]) -> Option<DateTime<FixedOffset>> {
+        None
+    }
+}
+
+/// The first activity after the last pause longer than `diff` seconds, and in another second,
+/// so the fights on each side of it do not share a second either.
+fn after_last_gap(activities: &[Activity], diff: i64) -> Option<DateTime<FixedOffset>> {
+    let mut dates: Vec<DateTime<FixedOffset>> = activities.iter().map(|activity| activity.date).collect();
+    dates.sort();
+
+    dates.windows(2).rev()
+        .find(|pair| pair[1] - pair[0] > Duration::seconds(diff) && pair[1].timestamp() > pair[0].timestamp())
+        .map(|pair| pair[1])
+}
+
+/// The strategies shipped with the parser, selected by `name` when deserialised.
+#[derive(Debug, PartialEq, Serialize, Deserialize, JsonSchema, Clone, Default)]
+#[serde(tag = "name", rename_all = "snake_case")]
+pub enum SplitStrategy {
+    /// A new fight starts after `time_between` seconds without activity.
+    #[default]
+    Gap,
+    /// As `Gap`, and a new fight also starts when only new opponents are seen for more than `window` seconds.
+    Opponent { window: i64 },
+    /// Fights of `length` seconds one after the other, the ones without activity are dropped.
+    Window { length: i64 },
+    /// The fights given by the user, whatever the activity.
+    Manual { markers: Vec<FightTimer> },
+    /// A fight is a run of `bucket` seconds periods holding at least `threshold` activities each.
+    Density { bucket: i64, threshold: usize },
+}
+
+impl SplitStrategy {
+    pub fn splitter(&self, time_between: i64, minimum_time: i64) -> Box<dyn FightSplitter> {
+        match self {
+            SplitStrategy::Gap => Box::new(GapSplitter { time_between, minimum_time }),
+            SplitStrategy::Opponent { window } => Box::new(OpponentSplitter { time_between, minimum_time, window: *window }),
+            SplitStrategy::Window { length } => Box::new(WindowSplitter { length: *length }),
+            SplitStrategy::Manual { markers } => Box::new(ManualSplitter { markers: markers.clone() }),
+            SplitStrategy::Density { bucket, threshold } => Box::new(DensitySplitter { bucket: *bucket, threshold: *threshold, minimum_time }),
+        }
+    }
+
+    /// An error when the strategy cannot split, as a window of no second.
+    pub fn check(&self) -> Result<(), String> {
+        match self {
+            SplitStrategy::Window { length } if *length < 1 || *length > MAX_WINDOW => {
+                Err(format!("window length {} does not fit, it goes from 1 to {} seconds", length, MAX_WINDOW))
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+/// The longest window, far above any log and far from the end of the dates.
+pub const MAX_WINDOW: i64 = i32::MAX as i64;
+
+/// Give another splitter only the activities of the listed kinds.
+pub struct KindSplitter {
+    pub kinds: Vec<ActivityKind>,
+    pub inner: Box<dyn FightSplitter>,
+}
+
+impl FightSplitter for KindSplitter {
+    fn split(&self, activities: &[Activity]) -> Vec<FightTimer> {
+        self.inner.split(&self.kept(activities))
+    }
+
+    fn settled(&self, activities: &[Activity]) -> Option<DateTime<FixedOffset>> {
+        self.inner.settled(&self.kept(activities))
+    }
+}
+
+impl KindSplitter {
+    fn kept(&self, activities: &[Activity]) -> Vec<Activity> {
+        activities.iter().filter(|activity| self.kinds.contains(&activity.kind)).copied().collect()
+    }
+}
+
+pub struct GapSplitter {
+    pub time_between: i64,
+    pub minimum_time: i64,
+}
+
+impl FightSplitter for GapSplitter {
+    fn split(&self, activities: &[Activity]) -> Vec<FightTimer> {
+        split_in_fight(activities.iter().map(|activity| activity.date).collect(), self.time_between, self.minimum_time)
+    }
+
+    fn settled(&self, activities: &[Activity]) -> Option<DateTime<FixedOffset>> {
+        after_last_gap(activities, self.time_between)
+    }
+}
+
+pub struct OpponentSplitter {
+    pub time_between: i64,
+    pub minimum_time: i64,
+    pub window: i64,
+}
+
+impl FightSplitter for OpponentSplitter {
+    fn split(&self, activities: &[Activity]) -> Vec<FightTimer> {
+        split_by_opponent(activities.to_vec(), self.time_between, self.minimum_time, self.window)
+    }
+
+    /// A pause ends a fight and forgets its opponents.
+    fn settled(&self, activities: &[Activity]) -> Option<DateTime<FixedOffset>> {
+        after_last_gap(activities, self.time_between)
+    }
+}
+
+pub struct WindowSplitter {
+    pub length: i64,
+}
+
+impl FightSplitter for WindowSplitter {
+    fn split(&self, activities: &[Activity]) -> Vec<FightTimer> {
+        split_in_window(activities.iter().map(|activity| activity.date).collect(), self.length)
+    }
+}
+
+pub struct ManualSplitter {
+    pub markers: Vec<FightTimer>,
+}
+
+impl FightSplitter for ManualSplitter {
+    /// The markers are cut to the dates of the activities, the ones outside are dropped.
+    fn split(&self, activities: &[Activity]) -> Vec<FightTimer> {
+        let dates = activities.iter().map(|activity| activity.date.timestamp());
+        let (first, last) = match (dates.clone().min(), dates.max()) {
+            (Some(first), Some(last)) => (first, last),
+            _ => return vec![],
+        };
+
+        let mut res: Vec<FightTimer> = self.markers.iter()
+            .map(|marker| FightTimer { start: marker.start.max(first), end: marker.end.min(last) })
+            .filter(|marker| marker.start <= marker.end)
+            .collect();
+        res.sort_by_key(|marker| marker.start);
+        res
+    }
+}
+
+pub struct DensitySplitter {
+    pub bucket: i64,
+    pub threshold: usize,
+    pub minimum_time: i64,
+}
+
+impl FightSplitter for DensitySplitter {
+    fn split(&self, activities: &[Activity]) -> Vec<FightTimer> {
+        split_by_density(activities.iter().map(|activity| activity.date).collect(), self.bucket, self.threshold, self.minimum_time)
+    }
+}
+
 pub fn split_in_fight(mut list: Vec<DateTime<FixedOffset>>, diff : i64, minimum : i64)-> Vec<FightTimer>{
 
     let diff_duration = Duration::seconds(diff);
@@ -18,13 +205,6 @@ pub fn split_in_fight(mut list: Vec<DateTime<FixedOffset>>, diff : i64, minimum
     if list.len() == 0 {
         return vec![]
     }
-    if list.len() == 1 {
-        return vec![FightTimer {
-            start: list.first().unwrap().timestamp(),
-            end: list.first().unwrap().timestamp(),
-        }]
-    }
-
     let mut res = vec![];
 
     list.sort();
@@ -56,6 +236,148 @@ pub fn split_in_fight(mut list: Vec<DateTime<FixedOffset>>, diff : i64, minimum
 }
 
 
+pub fn split_by_opponent(mut list: Vec<Activity>, diff: i64, minimum: i64, window: i64) -> Vec<FightTimer> {
+
+    let diff_duration = Duration::seconds(diff);
+    let minimum_duration = Duration::seconds(minimum);
+    let window_duration = Duration::seconds(window);
+
+    if list.len() < 2 {
+        return split_in_fight(list.iter().map(|activity| activity.date).collect(), diff, minimum);
+    }
+
+    let mut res = vec![];
+
+    list.sort_by_key(|activity| activity.date);
+
+    let mut start = list.first().unwrap().date;
+    let mut previous = start;
+    let mut current = HashSet::new();
+    let mut foreign = HashSet::new();
+    // date of the first activity against a new opponent, and of the activity before it
+    let mut foreign_since: Option<(DateTime<FixedOffset>, DateTime<FixedOffset>)> = None;
+
+    let push = |res: &mut Vec<FightTimer>, start: DateTime<FixedOffset>, end: DateTime<FixedOffset>| {
+        if end - start > minimum_duration {
+            res.push(FightTimer {
+                start: start.timestamp(),
+                end: end.timestamp()
+            });
+        }
+    };
+
+    for activity in list {
+        if activity.date - previous > diff_duration {
+            push(&mut res, start, previous);
+            start = activity.date;
+            current.clear();
+            foreign.clear();
+            foreign_since = None;
+        }
+
+        if let Some(opponent) = activity.opponent {
+            if current.is_empty() || current.contains(&opponent) {
+                current.insert(opponent);
+                current.extend(foreign.drain());
+                foreign_since = None;
+            } else {
+                foreign.insert(opponent);
+                let (since, before) = *foreign_since.get_or_insert((activity.date, previous));
+
+                if activity.date - since > window_duration {
+                    push(&mut res, start, before);
+                    start = since;
+                    current = std::mem::take(&mut foreign);
+                    foreign_since = None;
+                }
+            }
+        }
+
+        previous = activity.date;
+    }
+
+    push(&mut res, start, previous);
+
+    res
+}
+
+pub fn split_in_window(mut list: Vec<DateTime<FixedOffset>>, length: i64) -> Vec<FightTimer> {
+    let length = length.clamp(1, MAX_WINDOW);
+    let mut res: Vec<FightTimer> = vec![];
+
+    list.sort();
+
+    let first = match list.first() {
+        Some(first) => first.timestamp(),
+        None => return res,
+    };
+
+    for date in list {
+        let start = first + (date.timestamp() - first) / length * length;
+        if res.last().map(|timer| timer.start) != Some(start) {
+            res.push(FightTimer {
+                start,
+                end: start.saturating_add(length - 1)
+            });
+        }
+    }
+
+    res
+}
+
+pub fn split_by_density(mut list: Vec<DateTime<FixedOffset>>, bucket: i64, threshold: usize, minimum: i64) -> Vec<FightTimer> {
+    let bucket = bucket.max(1);
+    let mut res = vec![];
+
+    list.sort();
+
+    let first = match list.first() {
+        Some(first) => first.timestamp(),
+        None => return res,
+    };
+
+    // (bucket index, activity count, first and last activity of the bucket)
+    let mut buckets: Vec<(i64, usize, i64, i64)> = vec![];
+    for date in list {
+        let index = (date.timestamp() - first) / bucket;
+        match buckets.last_mut() {
+            Some(last) if last.0 == index => {
+                last.1 += 1;
+                last.3 = date.timestamp();
+            }
+            _ => buckets.push((index, 1, date.timestamp(), date.timestamp())),
+        }
+    }
+
+    let mut run: Option<(i64, i64, i64)> = None;
+    for (index, count, start, end) in buckets {
+        if count < threshold {
+            continue;
+        }
+
+        run = match run {
+            Some((last_index, run_start, _)) if last_index + 1 == index => Some((index, run_start, end)),
+            previous => {
+                if let Some((_, run_start, run_end)) = previous {
+                    if run_end - run_start > minimum {
+                        res.push(FightTimer { start: run_start, end: run_end });
+                    }
+                }
+                Some((index, start, end))
+            }
+        };
+    }
+
+    if let Some((_, run_start, run_end)) = run {
+        if run_end - run_start > minimum {
+            res.push(FightTimer { start: run_start, end: run_end });
+        }
+    }
+
+    res
+}
+
+
 #[cfg(test)]
 mod tests {
     // Note this useful idiom: importing names from outer (for mod tests) scope.
@@ -67,10 +389,7 @@ mod tests {
             split_in_fight(vec![
                 DateTime::parse_from_rfc3339("2021-03-17T20:30:45.111Z").unwrap()
             ],60,20),
-            vec![FightTimer {
-                start: DateTime::parse_from_rfc3339("2021-03-17T20:30:45.111Z").unwrap().timestamp(),
-                end: DateTime::parse_from_rfc3339("2021-03-17T20:30:45.111Z").unwrap().timestamp()
-            }]
+            vec![]
         )
     }
     #[test]
@@ -99,4 +418,175 @@ mod tests {
             },]
         )
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn assert_opponent_change_split() {
+        let date = |d: &str| DateTime::parse_from_rfc3339(d).unwrap();
+        let activity = |d: &str, opponent: Option<Symbol>| Activity { date: date(d), kind: ActivityKind::DamageDealt, opponent };
+        let mut names = crate::intern::Interner::new();
+        let cleric = Some(names.intern("Cleric Defender"));
+        let ranger = Some(names.intern("Ranger Defender"));
+        let knight = Some(names.intern("Knight Defender"));
+
+        let list = vec![
+            activity("2021-03-17T20:30:00.111Z", cleric),
+            activity("2021-03-17T20:30:05.111Z", ranger),
+            activity("2021-03-17T20:30:10.111Z", cleric),
+            activity("2021-03-17T20:30:12.111Z", None),
+            activity("2021-03-17T20:30:15.111Z", knight),
+            activity("2021-03-17T20:30:20.111Z", knight),
+            activity("2021-03-17T20:30:30.111Z", knight),
+        ];
+
+        assert_eq!(
+            split_in_fight(list.iter().map(|a| a.date).collect(), 30, 0).len(),
+            1
+        );
+        assert_eq!(
+            split_by_opponent(list, 30, 0, 10),
+            vec![FightTimer {
+                start: date("2021-03-17T20:30:00.111Z").timestamp(),
+                end: date("2021-03-17T20:30:12.111Z").timestamp()
+            }, FightTimer {
+                start: date("2021-03-17T20:30:15.111Z").timestamp(),
+                end: date("2021-03-17T20:30:30.111Z").timestamp()
+            }]
+        )
+    }
+
+    #[test]
+    fn assert_opponent_back_no_split() {
+        let date = |d: &str| DateTime::parse_from_rfc3339(d).unwrap();
+        let activity = |d: &str, opponent: Option<Symbol>| Activity { date: date(d), kind: ActivityKind::DamageDealt, opponent };
+        let mut names = crate::intern::Interner::new();
+        let cleric = Some(names.intern("Cleric Defender"));
+        let knight = Some(names.intern("Knight Defender"));
+
+        let list = vec![
+            activity("2021-03-17T20:30:00.111Z", cleric),
+            activity("2021-03-17T20:30:05.111Z", knight),
+            activity("2021-03-17T20:30:14.111Z", knight),
+            activity("2021-03-17T20:30:15.111Z", cleric),
+            activity("2021-03-17T20:30:25.111Z", knight),
+        ];
+
+        assert_eq!(
+            split_by_opponent(list, 30, 0, 10),
+            vec![FightTimer {
+                start: date("2021-03-17T20:30:00.111Z").timestamp(),
+                end: date("2021-03-17T20:30:25.111Z").timestamp()
+            }]
+        )
+    }
+
+    #[test]
+    fn assert_window_split() {
+        let date = |d: &str| DateTime::parse_from_rfc3339(d).unwrap();
+
+        assert_eq!(
+            split_in_window(vec![
+                date("2021-03-17T20:30:10.111Z"),
+                date("2021-03-17T20:30:00.111Z"),
+                date("2021-03-17T20:30:59.111Z"),
+                date("2021-03-17T20:32:05.111Z"),
+            ], 60),
+            vec![FightTimer {
+                start: date("2021-03-17T20:30:00.111Z").timestamp(),
+                end: date("2021-03-17T20:30:59.111Z").timestamp()
+            }, FightTimer {
+                start: date("2021-03-17T20:32:00.111Z").timestamp(),
+                end: date("2021-03-17T20:32:59.111Z").timestamp()
+            }]
+        )
+    }
+
+    #[test]
+    fn assert_density_split() {
+        let date = |d: &str| DateTime::parse_from_rfc3339(d).unwrap();
+
+        assert_eq!(
+            split_by_density(vec![
+                date("2021-03-17T20:30:00.111Z"),
+                date("2021-03-17T20:30:03.111Z"),
+                date("2021-03-17T20:30:06.111Z"),
+                date("2021-03-17T20:30:11.111Z"),
+                date("2021-03-17T20:30:13.111Z"),
+                date("2021-03-17T20:30:25.111Z"),
+                date("2021-03-17T20:30:41.111Z"),
+                date("2021-03-17T20:30:42.111Z"),
+                date("2021-03-17T20:30:43.111Z"),
+            ], 10, 2, 0),
+            vec![FightTimer {
+                start: date("2021-03-17T20:30:00.111Z").timestamp(),
+                end: date("2021-03-17T20:30:13.111Z").timestamp()
+            }, FightTimer {
+                start: date("2021-03-17T20:30:41.111Z").timestamp(),
+                end: date("2021-03-17T20:30:43.111Z").timestamp()
+            }]
+        )
+    }
+
+    #[test]
+    fn assert_strategy_by_name() {
+        let date = |d: &str| DateTime::parse_from_rfc3339(d).unwrap();
+        let activities: Vec<Activity> = vec![
+            "2021-03-17T20:30:00.111Z",
+            "2021-03-17T20:30:20.111Z",
+            "2021-03-17T20:32:00.111Z",
+            "2021-03-17T20:32:10.111Z",
+        ].into_iter().map(|d| Activity { date: date(d), kind: ActivityKind::DamageDealt, opponent: None }).collect();
+
+        assert_eq!(SplitStrategy::Gap.splitter(60, 0).split(&activities).len(), 2);
+        assert_eq!(SplitStrategy::Window { length: 300 }.splitter(60, 0).split(&activities).len(), 1);
+        let first = activities[0].date.timestamp();
+        let markers = vec![
+            FightTimer { start: first + 20, end: first + 30 },
+            FightTimer { start: first - 10, end: first + 10 },
+            FightTimer { start: i64::MAX - 10, end: i64::MAX },
+        ];
+        assert_eq!(
+            SplitStrategy::Manual { markers }.splitter(60, 0).split(&activities),
+            vec![FightTimer { start: first, end: first + 10 }, FightTimer { start: first + 20, end: first + 30 }]
+        );
+        assert_eq!(SplitStrategy::Manual { markers: vec![FightTimer { start: 0, end: 10 }] }.splitter(60, 0).split(&[]), vec![]);
+
+        assert!(SplitStrategy::Window { length: 300 }.check().is_ok());
+        assert!(SplitStrategy::Window { length: 0 }.check().is_err());
+        assert!(SplitStrategy::Window { length: i64::MAX }.check().is_err());
+        assert_eq!(SplitStrategy::Window { length: i64::MAX }.splitter(60, 0).split(&activities).len(), 1);
+    }
+
+    #[test]
+    fn assert_kind_filter() {
+        let date = |d: &str| DateTime::parse_from_rfc3339(d).unwrap();
+        let activity = |d: &str, kind: ActivityKind| Activity { date: date(d), kind, opponent: None };
+
+        let activities = vec![
+            activity("2021-03-17T20:30:00.111Z", ActivityKind::DamageDealt),
+            activity("2021-03-17T20:30:10.111Z", ActivityKind::DamageReceived),
+            activity("2021-03-17T20:30:30.111Z", ActivityKind::Resource),
+            activity("2021-03-17T20:30:50.111Z", ActivityKind::HealDone),
+            activity("2021-03-17T20:31:10.111Z", ActivityKind::DamageDealt),
+            activity("2021-03-17T20:31:20.111Z", ActivityKind::DamageDealt),
+        ];
+
+        assert_eq!(GapSplitter { time_between: 30, minimum_time: 0 }.split(&activities).len(), 1);
+        assert_eq!(GapSplitter { time_between: 30, minimum_time: 0 }.settled(&activities), None);
+
+        let splitter = KindSplitter {
+            kinds: DEFAULT_FIGHT_KINDS.to_vec(),
+            inner: Box::new(GapSplitter { time_between: 30, minimum_time: 0 }),
+        };
+        assert_eq!(splitter.settled(&activities), Some(date("2021-03-17T20:31:10.111Z")));
+        assert_eq!(
+            splitter.split(&activities),
+            vec![FightTimer {
+                start: date("2021-03-17T20:30:00.111Z").timestamp(),
+                end: date("2021-03-17T20:30:10.111Z").timestamp()
+            }, FightTimer {
+                start: date("2021-03-17T20:31:10.111Z").timestamp(),
+                end: date("2021-03-17T20:31:20.111Z").timestamp()
+            }]
+        );
+    }
+}
@@ -0,0 +1,123 @@
+use std::fs::{self, File};
+use std::io::{self, Read, Seek, SeekFrom};
+use std::path::{Path, PathBuf};
+
+/// What was written to the followed log since the last poll.
+#[derive(Debug, PartialEq)]
+pub enum Change {
+    /// New complete lines at the end of the same log.
+    Append(String),
+    /// The log was truncated or replaced by a new one, here are its complete lines from the start.
+    Restart(String),
+}
+
+/// Follow a log file, or the newest file of a directory, while the game writes it.
+pub struct Tail {
+    path: PathBuf,
+    current: Option<PathBuf>,
+    position: u64,
+}
+
+impl Tail {
+    pub fn new(path: &Path) -> Tail {
+        Tail {
+            path: path.to_path_buf(),
+            current: None,
+            position: 0,
+        }
+    }
+
+    /// The file being read, the newest one when following a directory.
+    pub fn current(&self) -> Option<&Path> {
+        self.current.as_deref()
+    }
+
+    pub fn poll(&mut self) -> io::Result<Option<Change>> {
+        let file = if self.path.is_dir() { newest(&self.path)? } else { Some(self.path.clone()) };
+        let file = match file {
+            Some(file) => file,
+            None => return Ok(None),
+        };
+
+        let length = fs::metadata(&file)?.len();
+        let restart = self.current.as_ref() != Some(&file) || length < self.position;
+        if restart {
+            self.current = Some(file.clone());
+            self.position = 0;
+        }
+
+        let text = self.read_lines(&file, length)?;
+        Ok(match (restart, text) {
+            (true, text) => Some(Change::Restart(text)),
+            (false, text) if text.is_empty() => None,
+            (false, text) => Some(Change::Append(text)),
+        })
+    }
+
+    /// The complete lines between the last position and `length`, the position moves past them.
+    fn read_lines(&mut self, file: &Path, length: u64) -> io::Result<String> {
+        if length <= self.position {
+            return Ok(String::new());
+        }
+
+        let mut reader = File::open(file)?;
+        reader.seek(SeekFrom::Start(self.position))?;
+        let mut bytes = vec![];
+        reader.take(length - self.position).read_to_end(&mut bytes)?;
+
+        let complete = match bytes.iter().rposition(|byte| *byte == b'\n') {
+            Some(end) => end + 1,
+            None => return Ok(String::new()),
+        };
+        bytes.truncate(complete);
+        self.position += complete as u64;
+
+        Ok(String::from_utf8_lossy(&bytes).into_owned())
+    }
+}
+
+/// The last modified file of a directory.
+fn newest(directory: &Path) -> io::Result<Option<PathBuf>> {
+    let mut newest = None;
+
+    for entry in fs::read_dir(directory)? {
+        let entry = entry?;
+        let metadata = entry.metadata()?;
+        if !metadata.is_file() {
+            continue;
+        }
+
+        let modified = metadata.modified()?;
+        if newest.as_ref().is_none_or(|(date, _)| modified > *date) {
+            newest = Some((modified, entry.path()));
+        }
+    }
+
+    Ok(newest.map(|(_, path)| path))
+}
+
+#[cfg(test)]
+mod tests {
+    // Note this useful idiom: importing names from outer (for mod tests) scope.
+    use super::*;
+    use std::fs::OpenOptions;
+    use std::io::Write;
+
+    #[test]
+    fn assert_tail_append_and_truncate() {
+        let path = std::env::temp_dir().join(format!("crowfall_tail_{}.txt", std::process::id()));
+        fs::write(&path, "first\nsecond\nthi").unwrap();
+
+        let mut tail = Tail::new(&path);
+        assert_eq!(tail.poll().unwrap(), Some(Change::Restart("first\nsecond\n".to_string())));
+        assert_eq!(tail.poll().unwrap(), None);
+
+        OpenOptions::new().append(true).open(&path).unwrap().write_all(b"rd\nfourth\n").unwrap();
+        assert_eq!(tail.poll().unwrap(), Some(Change::Append("third\nfourth\n".to_string())));
+
+        fs::write(&path, "again\n").unwrap();
+        assert_eq!(tail.poll().unwrap(), Some(Change::Restart("again\n".to_string())));
+
+        fs::remove_file(&path).unwrap();
+    }
+}
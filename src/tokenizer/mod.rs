@@ -0,0 +1,303 @@
+use chrono::prelude::*;
+
+/// Split `timestamp LEVEL CHANNEL - Channel _||_ Event=[...]` into the timestamp and the event text.
+pub fn split_line(line: &str) -> Option<(&str, &str)> {
+    let date = line.split(' ').next()?;
+    if !date.ends_with('Z') || !date.bytes().all(|b| b.is_ascii_digit() || b"-T:.Z".contains(&b)) {
+        return None;
+    }
+
+    let start = line.find("Event=[")? + "Event=[".len();
+    let end = line.rfind(']')?;
+    if end < start {
+        return None;
+    }
+
+    Some((date, &line[start..end]))
+}
+
+/// Read `2021-03-31T04:35:00.289Z` by position, anything else goes through chrono's rfc3339 parser.
+pub fn parse_timestamp(date: &str) -> Option<DateTime<FixedOffset>> {
+    let b = date.as_bytes();
+    if b.len() == 24 && b[4] == b'-' && b[7] == b'-' && b[10] == b'T' && b[13] == b':' && b[16] == b':' && b[19] == b'.' && b[23] == b'Z' {
+        let num = |from: usize, to: usize| -> Option<u32> {
+            b[from..to].iter().try_fold(0u32, |acc, c| {
+                if c.is_ascii_digit() { Some(acc * 10 + (c - b'0') as u32) } else { None }
+            })
+        };
+
+        let naive = NaiveDate::from_ymd_opt(num(0, 4)? as i32, num(5, 7)?, num(8, 10)?)?
+            .and_hms_milli_opt(num(11, 13)?, num(14, 16)?, num(17, 19)?, num(20, 23)?)?;
+
+        return Some(FixedOffset::east_opt(0)?.from_utc_datetime(&naive));
+    }
+
+    DateTime::parse_from_rfc3339(date).ok()
+}
+
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Verb {
+    Hit,
+    Healed,
+    Restored,
+    Drained,
+}
+
+#[derive(Debug, PartialEq)]
+pub struct Event<'a> {
+    pub verb: Verb,
+    pub emitter: &'a str,
+    pub spell: &'a str,
+    pub receiver: &'a str,
+    pub amount: u32,
+    pub absorbed: u32,
+    pub kind: &'a str,
+    pub critical: bool,
+}
+
+impl<'a> Event<'a> {
+    pub fn is_from_self(&self) -> bool {
+        self.emitter == "Your"
+    }
+
+    pub fn is_to_self(&self) -> bool {
+        self.receiver == "You"
+    }
+
+    pub fn is_food(&self) -> bool {
+        self.verb == Verb::Restored && self.emitter == "Your" && self.spell == "meal" && self.receiver == "You" && self.kind == "food"
+    }
+}
+
+/// Parse the "<emitter> <spell> hit|healed|restored|drained <receiver> for <amount> ..." grammar.
+pub fn parse_event(row: &str) -> Option<Event<'_>> {
+    let mut search = row.len();
+
+    // the receiver may contain " for ", so try from the last one backward until the tail reads
+    while let Some(pos) = row[..search].rfind(" for ") {
+        if let Some(event) = parse_around(&row[..pos], &row[pos + " for ".len()..]) {
+            return Some(event);
+        }
+        search = pos;
+    }
+
+    None
+}
+
+fn parse_around<'a>(head: &'a str, tail: &'a str) -> Option<Event<'a>> {
+    let (verb, source, receiver) = split_verb(head)?;
+
+    let digits = tail.bytes().take_while(|c| c.is_ascii_digit()).count();
+    if digits == 0 {
+        return None;
+    }
+    let amount = tail[..digits].parse::<u32>().ok()?;
+    let rest = &tail[digits..];
+
+    let (emitter, spell) = match source.find(' ') {
+        Some(pos) => (&source[..pos], &source[pos + 1..]),
+        None => (source, ""),
+    };
+
+    match verb {
+        Verb::Restored | Verb::Drained => {
+            if receiver != "You" {
+                return None;
+            }
+            let kind = rest.strip_prefix(' ')?.strip_suffix('.')?;
+            if kind.is_empty() {
+                return None;
+            }
+
+            Some(Event { verb, emitter, spell, receiver, amount, absorbed: 0, kind, critical: false })
+        }
+        Verb::Hit | Verb::Healed => {
+            if verb == Verb::Healed && spell.is_empty() {
+                return None;
+            }
+
+            let (absorbed, rest) = parse_absorbed(rest.strip_prefix(' ').unwrap_or(rest));
+            let rest = rest.strip_prefix(' ').unwrap_or(rest);
+            let rest = rest.strip_suffix('.')?;
+            let (critical, rest) = match rest.strip_suffix("(Critical)") {
+                Some(rest) => (true, rest.strip_suffix(' ').unwrap_or(rest)),
+                None => (false, rest),
+            };
+
+            let kind = match verb {
+                Verb::Hit if rest.is_empty() => "",
+                Verb::Hit => {
+                    let kind = rest.strip_suffix(" damage")?;
+                    if kind.is_empty() || kind.contains('(') {
+                        return None;
+                    }
+                    kind
+                }
+                _ if rest.is_empty() || rest == "hit points" => "",
+                _ => return None,
+            };
+
+            Some(Event { verb, emitter, spell, receiver, amount, absorbed, kind, critical })
+        }
+    }
+}
+
+/// Find the last verb of the head, what is before is the emitter and spell, what is after is the receiver.
+fn split_verb(head: &str) -> Option<(Verb, &str, &str)> {
+    let mut end = head.len();
+
+    while let Some(space) = head[..end].rfind(' ') {
+        let word_start = head[..space].rfind(' ').map(|p| p + 1).unwrap_or(0);
+        let verb = match &head[word_start..space] {
+            "hit" => Some(Verb::Hit),
+            "healed" => Some(Verb::Healed),
+            "restored" => Some(Verb::Restored),
+            "drained" => Some(Verb::Drained),
+            _ => None,
+        };
+
+        if let Some(verb) = verb {
+            if word_start > 0 && space + 1 < head.len() {
+                return Some((verb, &head[..word_start - 1], &head[space + 1..]));
+            }
+        }
+
+        end = space;
+    }
+
+    None
+}
+
+fn parse_absorbed(rest: &str) -> (u32, &str) {
+    let inner = match rest.strip_prefix('(') {
+        Some(inner) => inner,
+        None => return (0, rest),
+    };
+
+    let digits = inner.bytes().take_while(|c| c.is_ascii_digit()).count();
+    match inner[digits..].strip_prefix(" absorbed)") {
+        Some(after) if digits > 0 => (inner[..digits].parse::<u32>().unwrap_or(0), after),
+        _ => (0, rest),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    // Note this useful idiom: importing names from outer (for mod tests) scope.
+    use super::*;
+    use crate::dps::{parse_dps, RE_DPS};
+    use crate::heal::{parse_heal, RE_HEAL};
+    use crate::intern::Interner;
+    use std::fs::File;
+    use std::io::BufReader;
+    use std::io::prelude::*;
+
+    #[test]
+    fn assert_split_line() {
+        let line = "2021-03-31T04:36:01.225Z INFO    COMBAT    - Combat _||_ Event=[Your Static Bolt hit Cleric Defender for 100 Nature damage.]";
+        assert_eq!(
+            split_line(line),
+            Some(("2021-03-31T04:36:01.225Z", "Your Static Bolt hit Cleric Defender for 100 Nature damage."))
+        );
+        assert_eq!(split_line("some random text"), None);
+    }
+
+    #[test]
+    fn assert_parse_timestamp() {
+        assert_eq!(
+            parse_timestamp("2021-03-31T04:36:01.225Z"),
+            DateTime::parse_from_rfc3339("2021-03-31T04:36:01.225Z").ok()
+        );
+        assert_eq!(
+            parse_timestamp("2021-03-31T04:36:01Z"),
+            DateTime::parse_from_rfc3339("2021-03-31T04:36:01Z").ok()
+        );
+        assert_eq!(parse_timestamp("2021-13-31T04:36:01.225Z"), None);
+    }
+
+    #[test]
+    fn assert_event_dps() {
+        assert_eq!(
+            parse_event("Your Retaliate hit UDeadPRO for 292 (233 absorbed) Nature damage (Critical)."),
+            Some(Event {
+                verb: Verb::Hit,
+                emitter: "Your",
+                spell: "Retaliate",
+                receiver: "UDeadPRO",
+                amount: 292,
+                absorbed: 233,
+                kind: "Nature",
+                critical: true,
+            })
+        )
+    }
+
+    #[test]
+    fn assert_event_food() {
+        let event = parse_event("Your meal restored You for 300 food.").unwrap();
+        assert!(event.is_food());
+        assert_eq!(event.amount, 300);
+        assert!(!parse_event("Your Essence Burn restored You for 250 essence.").unwrap().is_food());
+    }
+
+    #[test]
+    fn assert_event_resource() {
+        assert_eq!(
+            parse_event("Your Essence Burn drained You for 100 essence."),
+            Some(Event {
+                verb: Verb::Drained,
+                emitter: "Your",
+                spell: "Essence Burn",
+                receiver: "You",
+                amount: 100,
+                absorbed: 0,
+                kind: "essence",
+                critical: false,
+            })
+        )
+    }
+
+    #[test]
+    fn assert_event_unknown() {
+        assert_eq!(parse_event("Your Shield Bash stunned Ranger Defender for 3 seconds."), None);
+        assert_eq!(parse_event("Your Shield Bash hit Ranger Defender for 3 seconds."), None);
+        assert_eq!(parse_event("Swoop healed You for 46 hit points."), None);
+    }
+
+    #[test]
+    fn assert_same_as_regex_on_fixture() {
+        let file = File::open("./fixtures/file1.txt").unwrap();
+        let mut buf_reader = BufReader::new(file);
+        let mut contents = String::new();
+        buf_reader.read_to_string(&mut contents).unwrap();
+
+        let mut names = Interner::new();
+
+        for line in contents.lines() {
+            let (date, row) = split_line(line).unwrap();
+            let dt = parse_timestamp(date).unwrap();
+            assert_eq!(dt, DateTime::parse_from_rfc3339(date).unwrap());
+
+            let event = parse_event(row).unwrap();
+
+            if RE_DPS.is_match(row) && event.verb == Verb::Hit {
+                let dps = parse_dps(row, dt, &mut names).unwrap();
+                assert_eq!(
+                    (names.resolve(dps.emitter), names.resolve(dps.spell), names.resolve(dps.receiver), dps.damage, dps.absorbed, names.resolve(dps.kind), dps.critical),
+                    (event.emitter, event.spell, event.receiver, event.amount, event.absorbed, event.kind, event.critical),
+                    "{}", row
+                );
+            } else if RE_HEAL.is_match(row) {
+                let heal = parse_heal(row, dt, &mut names).unwrap();
+                assert_eq!(event.verb, Verb::Healed, "{}", row);
+                assert_eq!(
+                    (names.resolve(heal.emitter), names.resolve(heal.spell), names.resolve(heal.receiver), heal.heal, heal.absorbed, heal.critical),
+                    (event.emitter, event.spell, event.receiver, event.amount, event.absorbed, event.critical),
+                    "{}", row
+                );
+            } else {
+                assert!(event.verb == Verb::Restored || event.verb == Verb::Drained, "{}", row);
+            }
+        }
+    }
+}
@@ -0,0 +1,98 @@
+use chrono::prelude::*;
+use std::collections::HashMap;
+use serde::{Serialize, Deserialize};
+use schemars::JsonSchema;
+
+#[derive(Debug, PartialEq, Serialize, Deserialize, JsonSchema, Clone)]
+pub struct UnknownEvent {
+    pub date: i64,
+    pub text: String,
+    pub tokens: Vec<String>,
+}
+
+pub fn parse_unknown(row: &str, dt: DateTime<FixedOffset>) -> UnknownEvent {
+    UnknownEvent {
+        date: dt.timestamp(),
+        text: row.to_string(),
+        tokens: row.split_whitespace().map(|token| token.to_string()).collect(),
+    }
+}
+
+/// The shape of a line is its text with every number replaced by `#`,
+/// so "Your meal restored You for 300 food." and "... for 150 food." are counted together.
+pub fn shape(row: &str) -> String {
+    let mut res = String::with_capacity(row.len());
+    let mut in_number = false;
+
+    for c in row.chars() {
+        if c.is_ascii_digit() {
+            if !in_number {
+                res.push('#');
+            }
+            in_number = true;
+        } else {
+            res.push(c);
+            in_number = false;
+        }
+    }
+
+    res
+}
+
+pub fn stats_unknown(list: &[UnknownEvent]) -> HashMap<String, u32> {
+    let mut shapes = HashMap::new();
+
+    for unknown in list.iter() {
+        let count = shapes.entry(shape(&unknown.text)).or_insert(0);
+        *count += 1;
+    }
+
+    shapes
+}
+
+#[cfg(test)]
+mod tests {
+    // Note this useful idiom: importing names from outer (for mod tests) scope.
+    use super::*;
+
+    #[test]
+    fn assert_unknown_tokens() {
+        let tt = "Your Shield Bash stunned Ranger Defender for 3 seconds.";
+        let dt = DateTime::parse_from_rfc3339("2021-03-17T20:30:45.111Z").unwrap();
+        assert_eq!(
+            parse_unknown(tt, dt),
+            UnknownEvent {
+                date: dt.timestamp(),
+                text: tt.to_string(),
+                tokens: ["Your", "Shield", "Bash", "stunned", "Ranger", "Defender", "for", "3", "seconds."]
+                    .iter().map(|token| token.to_string()).collect(),
+            }
+        )
+    }
+
+    #[test]
+    fn assert_shape_normalise_numbers() {
+        assert_eq!(
+            shape("Your Shield Bash stunned Ranger Defender for 3 seconds (1250 absorbed)."),
+            "Your Shield Bash stunned Ranger Defender for # seconds (# absorbed)."
+        )
+    }
+
+    #[test]
+    fn assert_stats_unknown_count_by_shape() {
+        let dt = DateTime::from(Utc::now());
+        let list = vec![
+            parse_unknown("Your Shield Bash stunned Ranger Defender for 3 seconds.", dt),
+            parse_unknown("Your Shield Bash stunned Ranger Defender for 12 seconds.", dt),
+            parse_unknown("You died.", dt),
+        ];
+
+        let mut res: HashMap<String, u32> = HashMap::new();
+        res.insert("Your Shield Bash stunned Ranger Defender for # seconds.".to_string(), 2);
+        res.insert("You died.".to_string(), 1);
+        assert_eq!(
+            stats_unknown(&list),
+            res
+        )
+    }
+}
@@ -3,14 +3,15 @@
 const fileSelector = document.getElementById('file-selector');
 const timeBetween = document.getElementById('time-between');
 const timeMinimum = document.getElementById('minimum-time');
+const strict = document.getElementById('strict');
+const splitStrategy = document.getElementById('split-strategy');
+const opponentWindow = document.getElementById('opponent-window');
+const windowLength = document.getElementById('window-length');
+const densityThreshold = document.getElementById('density-threshold');
+const densityBucket = document.getElementById('density-bucket');
 
 fileSelector.addEventListener('change', (event) => {
-
-    const fileList = event.target.files;
-    for (const file of fileList) {
-        readFile(file)
-    }
-
+    readFiles(event.target.files)
 });
 
 var chart_emit_by_kind = null;
@@ -25,43 +26,131 @@ fight_list.addEventListener('change', (event)=>{
     console.timeEnd("display");
 })
 
-function readFile(file) {
-    const reader = new FileReader();
+var session = null;
 
+timeBetween.addEventListener('change', resplit);
+timeMinimum.addEventListener('change', resplit);
+splitStrategy.addEventListener('change', resplit);
 
-    reader.addEventListener('load', (event) => {
+// an empty field is left undefined, the parser then uses its default
+function number(input) {
+    let value = parseInt(input.value, 10);
+    return Number.isNaN(value) ? undefined : value;
+}
 
-        console.time("parse");
+function currentOptions() {
+    return {
+        time_between: number(timeBetween),
+        minimum_time: number(timeMinimum),
+        strict: strict.checked,
+        patterns: window.patterns,
+        fight_kinds: window.fightKinds,
+        strategy: {
+            name: splitStrategy.value,
+            window: number(opponentWindow),
+            length: number(windowLength),
+            bucket: number(densityBucket),
+            threshold: number(densityThreshold),
+        },
+    };
+}
 
-        let res = window.parse( event.target.result, BigInt(timeBetween.value,10), BigInt(timeMinimum.value,10) )
+// several files are the logs of several launches, parsed as one session
+async function readFiles(fileList) {
+    const texts = await Promise.all(Array.from(fileList, (file) => file.text()));
 
-        console.timeEnd("parse");
-        console.time("display");
+    console.time("parse");
 
-        if (res.errors.length> 0){
-            alert("cannot parse the following lines : \n" + res.errors.join("\n"))
-        }
+    if (session != null) {
+        session.free();
+        session = null;
+    }
+    try {
+        session = texts.length === 1
+            ? new window.Session(texts[0], currentOptions())
+            : window.Session.fromFiles(texts, currentOptions());
+    } catch (error) {
+        alert("invalid options : " + error);
+        return;
+    }
 
-        fights = res.fights;
+    console.timeEnd("parse");
 
-        fight_list.innerHTML="";
-        for (var i = 0; i < fights.length; i++){
-            let st = new Date(fights[i].time.start * 1000);
-            let nd = new Date(fights[i].time.end *1000);
-            fight_list.options[fight_list.options.length]=  new Option(st.toLocaleTimeString() + " -> " + nd.toLocaleTimeString() + " : "+ fights[i].opponent.join(", "), i)
-        }
+    let res = resplit();
+    if (res == null) {
+        return;
+    }
 
-        if (res.fights[0]){
-            render_all_timer(0)
-        }
-        console.timeEnd("display");
+    if (res.errors.length> 0){
+        alert("cannot parse the following lines : \n" + res.errors.join("\n"))
+    }
 
-    });
-    reader.readAsText(file);
+    if (res.warnings.length> 0){
+        console.warn("unknown lines by shape", res.unknown_shapes)
+    }
+}
+
+function resplit() {
+    if (session == null) {
+        return null;
+    }
 
+    console.time("split");
+    let options = currentOptions();
+    let res;
+    try {
+        session.setOptions(options);
+        res = session.resplit();
+    } catch (error) {
+        console.timeEnd("split");
+        alert("invalid options : " + error);
+        return null;
+    }
+    console.timeEnd("split");
 
+    console.time("display");
+    fights = res.fights;
+
+    fight_list.innerHTML="";
+    for (var i = 0; i < fights.length; i++){
+        let st = new Date(fights[i].time.start * 1000);
+        let nd = new Date(fights[i].time.end *1000);
+        fight_list.options[fight_list.options.length]=  new Option(st.toLocaleTimeString() + " -> " + nd.toLocaleTimeString() + " : "+ fights[i].opponent.join(", "), i)
+    }
+    // the fight csv need a fight
+    csv_events.disabled = csv_spells.disabled = fights.length === 0;
+
+    if (res.fights[0]){
+        render_all_timer(0)
+    }
+    console.timeEnd("display");
+
+    return res;
+}
+
+function download(name, text, type = 'text/csv') {
+    let link = document.createElement('a');
+    link.href = URL.createObjectURL(new Blob([text], { type: type }));
+    link.download = name;
+    link.click();
+    URL.revokeObjectURL(link.href);
 }
 
+document.getElementById('csv-fights').addEventListener('click', () => {
+    if (session != null) download("fights.csv", session.fightsCsv());
+});
+const csv_events = document.getElementById('csv-events');
+const csv_spells = document.getElementById('csv-spells');
+csv_events.addEventListener('click', () => {
+    if (session != null && fight_list.value !== "") download("events.csv", session.eventsCsv(parseInt(fight_list.value, 10)));
+});
+csv_spells.addEventListener('click', () => {
+    if (session != null && fight_list.value !== "") download("spells.csv", session.spellsCsv(parseInt(fight_list.value, 10)));
+});
+document.getElementById('html-report').addEventListener('click', () => {
+    if (session != null) download("report.html", session.htmlReport(), 'text/html');
+});
+
 var chart_by_id ={};
 
 
@@ -198,4 +287,31 @@ function render_timer(id, data, colors){
     var chart = new ApexCharts(document.querySelector(id), options);
     chart.render();
     chart_by_id[id] = chart
- }
\ No newline at end of file
+ }
+// served by `crowfall serve`, the fights of the log being written come over a WebSocket
+function connectLive() {
+    if (location.hostname !== '127.0.0.1' && location.hostname !== 'localhost') {
+        return;
+    }
+
+    const socket = new WebSocket('ws://' + location.host + '/live');
+    socket.addEventListener('message', (event) => {
+        // a file chosen by hand stays on screen
+        if (session != null) {
+            return;
+        }
+
+        const live = JSON.parse(event.data);
+        const fight = live.current || live.last;
+        if (fight == null) {
+            return;
+        }
+
+        fights = [fight];
+        fight_list.innerHTML = "";
+        fight_list.options[0] = new Option((live.current ? "live : " : "last : ") + fight.opponent.join(", "), 0);
+        render_all_timer(0);
+    });
+}
+
+connectLive();
@@ -0,0 +1,47 @@
+// Parse a log away from the page, load it with `new Worker('worker.js', { type: 'module' })`.
+//
+// messages received :
+//   { type: 'parse', text, options, cancel }  cancel is an optional Int32Array on a SharedArrayBuffer, set it to 1 to stop
+//   { type: 'resplit', timeBetween, minimumTime }  both optional, the ones of the options by default
+// messages sent :
+//   { type: 'progress', progress }  progress is { lines, bytes, events, total_bytes }
+//   { type: 'done', result }        result is the same as `parse`
+//   { type: 'cancelled' }
+//   { type: 'error', message }
+
+import init, { Session } from './pkg/crowfall_parser.js';
+
+const ready = init();
+let session = null;
+
+self.onmessage = async (event) => {
+    await ready;
+    const message = event.data;
+
+    try {
+        if (message.type === 'parse') {
+            if (session != null) {
+                session.free();
+                session = null;
+            }
+
+            session = Session.withProgress(message.text, message.options, (progress) => {
+                self.postMessage({ type: 'progress', progress: progress });
+                return message.cancel == null || Atomics.load(message.cancel, 0) === 0;
+            });
+
+            // the times of the options, or their defaults when left out
+            self.postMessage({ type: 'done', result: session.resplit() });
+        }
+
+        if (message.type === 'resplit' && session != null) {
+            self.postMessage({ type: 'done', result: session.resplit(message.timeBetween, message.minimumTime) });
+        }
+    } catch (error) {
+        if (error === 'cancelled') {
+            self.postMessage({ type: 'cancelled' });
+        } else {
+            self.postMessage({ type: 'error', message: String(error) });
+        }
+    }
+};